@@ -0,0 +1,196 @@
+//! Errors returned by the credential providers.
+
+use crate::CredentialProvider;
+use serde_xml_rs as serde_xml;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Why credentials could not be loaded.
+///
+/// Converts into `anyhow::Error` like any other error type.
+#[derive(Error, Debug)]
+pub enum CredentialsError {
+    /// Every provider of the chain tried by `Credentials::new` failed, along
+    /// with the reason each of them did.
+    #[error("no credentials found ({})", format_attempts(.0))]
+    NoCredentialsFound(Vec<(CredentialProvider, CredentialsError)>),
+    /// The profile is in neither the shared credentials nor the config file,
+    /// at `paths`.
+    #[error("profile {name} not found in {}", format_paths(.paths))]
+    ProfileMissing { name: String, paths: Vec<PathBuf> },
+    /// A profile lacks the `name` key, e.g. `aws_secret_access_key`.
+    /// `location` names the profile and the files it was read from.
+    #[error("Missing {name} in {location}")]
+    MissingSetting { name: String, location: String },
+    /// A provider is missing some setting, or it is invalid.
+    #[error("{0}")]
+    Config(String),
+    /// Not running on EC2 or ECS, or the metadata service couldn't be reached.
+    /// Requests to it that time out are reported as `Timeout` instead.
+    #[error("instance metadata unavailable: {0}")]
+    MetadataUnavailable(String),
+    #[error(transparent)]
+    Sts(#[from] StsError),
+    #[error("HTTP request failed: {0}")]
+    Http(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// An HTTP request didn't complete within its connect or read timeout.
+    #[error("HTTP request timed out: {0}")]
+    Timeout(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// An endpoint expected to return JSON answered with an error status or
+    /// something else, such as an HTML error page. `body` is truncated.
+    #[error("unexpected HTTP {status} response: {body}")]
+    UnexpectedResponse { status: u16, body: String },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// A file, response or setting couldn't be parsed.
+    #[error("failed to parse {0}")]
+    Parse(String),
+}
+
+pub(crate) fn format_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" and ")
+}
+
+fn format_attempts(attempts: &[(CredentialProvider, CredentialsError)]) -> String {
+    attempts
+        .iter()
+        .map(|(provider, error)| format!("{}: {}", provider, error))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Result of the fallible functions of this crate.
+pub type Result<T> = std::result::Result<T, CredentialsError>;
+
+impl From<attohttpc::Error> for CredentialsError {
+    fn from(e: attohttpc::Error) -> Self {
+        match e.kind() {
+            // Read timeouts surface as `WouldBlock` on some platforms
+            attohttpc::ErrorKind::Io(io)
+                if matches!(
+                    io.kind(),
+                    std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+                ) =>
+            {
+                CredentialsError::Timeout(Box::new(e))
+            }
+            _ => CredentialsError::Http(Box::new(e)),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl From<reqwest::Error> for CredentialsError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            CredentialsError::Timeout(Box::new(e))
+        } else {
+            CredentialsError::Http(Box::new(e))
+        }
+    }
+}
+
+impl From<ini::Error> for CredentialsError {
+    fn from(e: ini::Error) -> Self {
+        match e {
+            ini::Error::Io(e) => CredentialsError::Io(e),
+            ini::Error::Parse(e) => CredentialsError::Parse(format!("INI: {}", e)),
+        }
+    }
+}
+
+impl From<serde_xml::Error> for CredentialsError {
+    fn from(e: serde_xml::Error) -> Self {
+        CredentialsError::Parse(format!("XML: {}", e))
+    }
+}
+
+impl From<serde_json::Error> for CredentialsError {
+    fn from(e: serde_json::Error) -> Self {
+        CredentialsError::Parse(format!("JSON: {}", e))
+    }
+}
+
+impl From<url::ParseError> for CredentialsError {
+    fn from(e: url::ParseError) -> Self {
+        CredentialsError::Parse(format!("URL: {}", e))
+    }
+}
+
+impl From<std::num::ParseIntError> for CredentialsError {
+    fn from(e: std::num::ParseIntError) -> Self {
+        CredentialsError::Parse(format!("integer: {}", e))
+    }
+}
+
+/// Error returned by STS, such as `AccessDenied` when a role can't be assumed
+/// or `ExpiredToken` when the credentials or MFA code used are stale.
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+#[error("STS: {code}: {message}")]
+pub struct StsError {
+    pub code: String,
+    pub message: String,
+}
+
+impl StsError {
+    /// Parse an STS `<ErrorResponse>` document.
+    pub(crate) fn from_response(body: &str) -> Option<StsError> {
+        let error = serde_xml::from_str::<StsErrorResponse>(body).ok()?.error;
+        Some(StsError {
+            code: error.code,
+            message: error.message,
+        })
+    }
+
+    pub fn is_access_denied(&self) -> bool {
+        self.code == "AccessDenied"
+    }
+
+    pub fn is_expired_token(&self) -> bool {
+        self.code == "ExpiredToken" || self.code == "ExpiredTokenException"
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct StsErrorResponse {
+    #[serde(rename = "Error")]
+    error: StsErrorDetails,
+}
+
+#[derive(Deserialize, Debug)]
+struct StsErrorDetails {
+    #[serde(rename = "Code")]
+    code: String,
+    #[serde(rename = "Message")]
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_credentials_found_lists_attempts() {
+        let error = CredentialsError::NoCredentialsFound(vec![
+            (
+                CredentialProvider::Environment,
+                CredentialsError::Config("AWS_ACCESS_KEY_ID is not set".into()),
+            ),
+            (
+                CredentialProvider::Profile,
+                CredentialsError::ProfileMissing {
+                    name: "default".into(),
+                    paths: vec!["/home/user/.aws/credentials".into()],
+                },
+            ),
+        ]);
+        assert_eq!(
+            error.to_string(),
+            "no credentials found (environment: AWS_ACCESS_KEY_ID is not set; profile: profile default not found in /home/user/.aws/credentials)"
+        );
+    }
+}
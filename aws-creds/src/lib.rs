@@ -4,10 +4,23 @@
 #[macro_use]
 extern crate serde_derive;
 
+mod cache;
 mod credentials;
+mod error;
+mod profile;
+mod provider;
+pub mod signing;
+pub use cache::CachedCredentialsProvider;
 pub use credentials::*;
+pub use error::{CredentialsError, Result, StsError};
+pub use profile::Profile;
+pub use provider::{
+    default_chain, CredentialsProvider, EnvironmentProvider, InstanceMetadataProvider,
+    ProfileProvider, WebIdentityProvider,
+};
 
 #[cfg(test)]
+#[allow(dead_code)]
 mod test {
     use crate::Credentials;
     use std::env;
@@ -0,0 +1,209 @@
+//! Minimal [AWS V4 Signing][link] for the STS calls issued by this crate.
+//! The primitives deriving keys and signatures are shared with the `s3`
+//! crate, which builds its own canonical requests.
+//!
+//! [link]: https://docs.aws.amazon.com/general/latest/gr/signature-version-4.html
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac, NewMac};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use sha2::{Digest, Sha256};
+
+const SHORT_DATE: &str = "%Y%m%d";
+const LONG_DATETIME: &str = "%Y%m%dT%H%M%SZ";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Everything but the unreserved characters `A-Za-z0-9-_.~`.
+const QUERY_ENCODE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// Generate a canonical query string, parameters sorted by key and encoded
+/// following AWS's rules. Also usable as the actual query of the request.
+pub(crate) fn canonical_query_string(params: &[(&str, &str)]) -> String {
+    let mut params: Vec<(String, String)> = params
+        .iter()
+        .map(|(key, value)| {
+            (
+                utf8_percent_encode(key, QUERY_ENCODE).to_string(),
+                utf8_percent_encode(value, QUERY_ENCODE).to_string(),
+            )
+        })
+        .collect();
+    params.sort();
+    params
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Generate the signing key for the given date, region and service.
+pub fn signing_key(
+    datetime: &DateTime<Utc>,
+    secret_key: &str,
+    region: &str,
+    service: &str,
+) -> Vec<u8> {
+    let secret = format!("AWS4{}", secret_key);
+    let mut key = secret.into_bytes();
+    for part in &[
+        datetime.format(SHORT_DATE).to_string().as_str(),
+        region,
+        service,
+        "aws4_request",
+    ] {
+        let mut hmac = HmacSha256::new_varkey(&key).expect("HMAC can take a key of any size");
+        hmac.update(part.as_bytes());
+        key = hmac.finalize().into_bytes().to_vec();
+    }
+    key
+}
+
+/// Generate the scope of the signing key for the given date, region and
+/// service, as found in the `Credential` of signed requests.
+pub fn scope_string(datetime: &DateTime<Utc>, region: &str, service: &str) -> String {
+    format!(
+        "{}/{}/{}/aws4_request",
+        datetime.format(SHORT_DATE),
+        region,
+        service
+    )
+}
+
+/// Generate the "string to sign" of a canonical request, signed with a key
+/// of the given scope.
+pub fn string_to_sign(datetime: &DateTime<Utc>, scope: &str, canonical_request: &str) -> String {
+    format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        datetime.format(LONG_DATETIME),
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    )
+}
+
+/// Hex encoded signature of `string_to_sign`, or of a POST policy.
+pub fn sign(signing_key: &[u8], string_to_sign: &str) -> String {
+    let mut hmac = HmacSha256::new_varkey(signing_key).expect("HMAC can take a key of any size");
+    hmac.update(string_to_sign.as_bytes());
+    hex::encode(hmac.finalize().into_bytes())
+}
+
+/// Sign a bodiless `GET https://{host}/?{query}` request, returns the headers
+/// to send along with it, `Authorization` included.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn signed_get_headers(
+    host: &str,
+    query: &str,
+    region: &str,
+    service: &str,
+    datetime: &DateTime<Utc>,
+    access_key: &str,
+    secret_key: &str,
+    session_token: Option<&str>,
+) -> Vec<(&'static str, String)> {
+    let mut headers = vec![
+        ("host", host.to_string()),
+        ("x-amz-date", datetime.format(LONG_DATETIME).to_string()),
+    ];
+    if let Some(token) = session_token {
+        headers.push(("x-amz-security-token", token.to_string()));
+    }
+
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "GET\n/\n{}\n{}\n{}\n{}",
+        query,
+        canonical_headers,
+        signed_headers,
+        hex::encode(Sha256::digest(b""))
+    );
+
+    let scope = scope_string(datetime, region, service);
+    let string_to_sign = string_to_sign(datetime, &scope, &canonical_request);
+    let key = signing_key(datetime, secret_key, region, service);
+    let signature = sign(&key, &string_to_sign);
+
+    headers.push((
+        "authorization",
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{},SignedHeaders={},Signature={}",
+            access_key, scope, signed_headers, signature
+        ),
+    ));
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_canonical_query_string() {
+        let query = canonical_query_string(&[
+            ("Version", "2011-06-15"),
+            ("RoleArn", "arn:aws:iam::123456789012:role/demo"),
+            ("Action", "AssumeRole"),
+            ("RoleSessionName", "a b~c"),
+        ]);
+        assert_eq!(
+            query,
+            "Action=AssumeRole\
+             &RoleArn=arn%3Aaws%3Aiam%3A%3A123456789012%3Arole%2Fdemo\
+             &RoleSessionName=a%20b~c\
+             &Version=2011-06-15"
+        );
+    }
+
+    #[test]
+    fn test_sign() {
+        // `get-vanilla` of the AWS Signature Version 4 test suite
+        let datetime = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+        let scope = scope_string(&datetime, "us-east-1", "service");
+        assert_eq!(scope, "20150830/us-east-1/service/aws4_request");
+        let string_to_sign = string_to_sign(
+            &datetime,
+            &scope,
+            "GET\n/\n\nhost:example.amazonaws.com\nx-amz-date:20150830T123600Z\n\n\
+             host;x-amz-date\ne3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        );
+        let key = signing_key(
+            &datetime,
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "us-east-1",
+            "service",
+        );
+        assert_eq!(
+            sign(&key, &string_to_sign),
+            "5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn test_signing_key() {
+        // https://docs.aws.amazon.com/general/latest/gr/signature-v4-examples.html
+        let datetime = Utc.with_ymd_and_hms(2012, 2, 15, 0, 0, 0).unwrap();
+        let key = signing_key(
+            &datetime,
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+}
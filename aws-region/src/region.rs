@@ -68,6 +68,10 @@ pub enum Region {
     MeSouth1,
     /// sa-east-1
     SaEast1,
+    /// us-gov-east-1
+    UsGovEast1,
+    /// us-gov-west-1
+    UsGovWest1,
     /// Digital Ocean nyc3
     DoNyc3,
     /// Digital Ocean ams3
@@ -114,6 +118,8 @@ impl fmt::Display for Region {
             EuWest3 => write!(f, "eu-west-3"),
             SaEast1 => write!(f, "sa-east-1"),
             MeSouth1 => write!(f, "me-south-1"),
+            UsGovEast1 => write!(f, "us-gov-east-1"),
+            UsGovWest1 => write!(f, "us-gov-west-1"),
             DoNyc3 => write!(f, "nyc3"),
             DoAms3 => write!(f, "ams3"),
             DoSgp1 => write!(f, "sgp1"),
@@ -123,7 +129,7 @@ impl fmt::Display for Region {
             WaUsEast2 => write!(f, "us-east-2"),
             WaUsWest1 => write!(f, "us-west-1"),
             WaEuCentral1 => write!(f, "eu-central-1"),
-            Custom { ref region, .. } => write!(f, "{}", region.to_string()),
+            Custom { ref region, .. } => write!(f, "{}", region),
         }
     }
 }
@@ -154,6 +160,8 @@ impl FromStr for Region {
             "eu-west-3" => Ok(EuWest3),
             "sa-east-1" => Ok(SaEast1),
             "me-south-1" => Ok(MeSouth1),
+            "us-gov-east-1" => Ok(UsGovEast1),
+            "us-gov-west-1" => Ok(UsGovWest1),
             "nyc3" => Ok(DoNyc3),
             "ams3" => Ok(DoAms3),
             "sgp1" => Ok(DoSgp1),
@@ -198,6 +206,8 @@ impl Region {
             EuWest3 => String::from("s3-eu-west-3.amazonaws.com"),
             SaEast1 => String::from("s3-sa-east-1.amazonaws.com"),
             MeSouth1 => String::from("s3-me-south-1.amazonaws.com"),
+            UsGovEast1 => String::from("s3.us-gov-east-1.amazonaws.com"),
+            UsGovWest1 => String::from("s3.us-gov-west-1.amazonaws.com"),
             DoNyc3 => String::from("nyc3.digitaloceanspaces.com"),
             DoAms3 => String::from("ams3.digitaloceanspaces.com"),
             DoSgp1 => String::from("sgp1.digitaloceanspaces.com"),
@@ -211,6 +221,18 @@ impl Region {
         }
     }
 
+    /// AWS partition of the region, guessed from its name for custom and
+    /// non-AWS regions.
+    pub fn partition(&self) -> Partition {
+        Partition::from_region_name(&self.to_string())
+    }
+
+    /// Host of the regional STS endpoint, such as `sts.eu-west-1.amazonaws.com`
+    /// or `sts.cn-north-1.amazonaws.com.cn`.
+    pub fn sts_host(&self) -> String {
+        format!("sts.{}.{}", self, self.partition().dns_suffix())
+    }
+
     pub fn scheme(&self) -> String {
         match *self {
             Region::Custom { ref endpoint, .. } => match endpoint.find("://") {
@@ -232,6 +254,84 @@ impl Region {
     }
 }
 
+/// Group of AWS regions sharing a domain, and isolated from the other
+/// partitions: credentials of one partition aren't valid in another.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Partition {
+    /// aws, the standard regions
+    Aws,
+    /// aws-cn, China
+    AwsCn,
+    /// aws-us-gov, AWS GovCloud (US)
+    AwsUsGov,
+    /// aws-iso, US ISO East
+    AwsIso,
+    /// aws-iso-b, US ISOB East
+    AwsIsoB,
+}
+
+impl Partition {
+    /// Partition of a region name such as `cn-north-1`, regions that aren't
+    /// recognised as belonging to another partition are in `aws`.
+    pub fn from_region_name(region: &str) -> Partition {
+        if region.starts_with("cn-") {
+            Partition::AwsCn
+        } else if region.starts_with("us-gov-") {
+            Partition::AwsUsGov
+        } else if region.starts_with("us-isob-") {
+            Partition::AwsIsoB
+        } else if region.starts_with("us-iso-") {
+            Partition::AwsIso
+        } else {
+            Partition::Aws
+        }
+    }
+
+    /// Domain under which the partition's service endpoints live.
+    pub fn dns_suffix(&self) -> &'static str {
+        match *self {
+            Partition::Aws | Partition::AwsUsGov => "amazonaws.com",
+            Partition::AwsCn => "amazonaws.com.cn",
+            Partition::AwsIso => "c2s.ic.gov",
+            Partition::AwsIsoB => "sc2s.sgov.gov",
+        }
+    }
+}
+
+impl fmt::Display for Partition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Partition::Aws => write!(f, "aws"),
+            Partition::AwsCn => write!(f, "aws-cn"),
+            Partition::AwsUsGov => write!(f, "aws-us-gov"),
+            Partition::AwsIso => write!(f, "aws-iso"),
+            Partition::AwsIsoB => write!(f, "aws-iso-b"),
+        }
+    }
+}
+
+#[test]
+fn partitions() {
+    let region: Region = "us-gov-east-1".parse().unwrap();
+    assert_eq!(region, Region::UsGovEast1);
+    assert_eq!(region.partition(), Partition::AwsUsGov);
+    assert_eq!(region.partition().to_string(), "aws-us-gov");
+    assert_eq!(region.sts_host(), "sts.us-gov-east-1.amazonaws.com");
+
+    let region: Region = "cn-north-1".parse().unwrap();
+    assert_eq!(region.partition(), Partition::AwsCn);
+    assert_eq!(region.sts_host(), "sts.cn-north-1.amazonaws.com.cn");
+
+    let region: Region = "us-isob-east-1".parse().unwrap();
+    assert_eq!(region.to_string(), "us-isob-east-1");
+    assert_eq!(region.partition(), Partition::AwsIsoB);
+    assert_eq!(region.sts_host(), "sts.us-isob-east-1.sc2s.sgov.gov");
+
+    let region: Region = "eu-west-1".parse().unwrap();
+    assert_eq!(region.partition(), Partition::Aws);
+    assert_eq!(region.sts_host(), "sts.eu-west-1.amazonaws.com");
+}
+
 #[test]
 fn yandex_object_storage() {
     let yandex = Region::Custom {
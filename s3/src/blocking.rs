@@ -7,11 +7,12 @@ use attohttpc::header::HeaderName;
 
 use super::bucket::Bucket;
 use super::command::Command;
+use crate::checksum::ChecksumVerifier;
 use chrono::{DateTime, Utc};
 
 use crate::command::HttpMethod;
-use crate::request_trait::Request;
-use anyhow::anyhow;
+use crate::request_trait::{check_content_length, Request, RequestTimeout};
+use crate::serde_types::S3Error;
 use anyhow::Result;
 // static CLIENT: Lazy<Client> = Lazy::new(|| {
 //     if cfg!(feature = "no-verify-ssl") {
@@ -30,6 +31,7 @@ pub struct AttoRequest<'a> {
     pub bucket: &'a Bucket,
     pub path: &'a str,
     pub command: Command<'a>,
+    /// Time the request is signed at, `Utc::now()` unless set otherwise.
     pub datetime: DateTime<Utc>,
     pub sync: bool,
 }
@@ -46,7 +48,7 @@ impl<'a> Request for AttoRequest<'a> {
         self.bucket.clone()
     }
 
-    fn command(&self) -> Command {
+    fn command(&self) -> Command<'_> {
         self.command.clone()
     }
 
@@ -56,12 +58,29 @@ impl<'a> Request for AttoRequest<'a> {
 
     fn response(&self) -> Result<Self::Response> {
         // Build headers
-        let headers = match self.headers() {
-            Ok(headers) => headers,
-            Err(e) => return Err(e),
-        };
+        let headers = self.headers()?;
 
         let mut session = attohttpc::Session::new();
+        if let Some(timeout) = self.bucket.request_timeout() {
+            session.connect_timeout(timeout);
+            session.timeout(timeout);
+        }
+        session.danger_accept_invalid_certs(self.bucket.danger_accept_invalid_certs());
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "sync-native-tls")] {
+                for certificate in self.bucket.extra_root_certificates() {
+                    session.add_root_certificate(native_tls::Certificate::from_der(certificate.der())?);
+                }
+            } else if #[cfg(feature = "sync-rustls-tls")] {
+                for certificate in self.bucket.extra_root_certificates() {
+                    session.add_root_certificate(rustls::Certificate(certificate.der().to_vec()));
+                }
+            } else {
+                if self.bucket.danger_accept_invalid_certs() || !self.bucket.extra_root_certificates().is_empty() {
+                    return Err(anyhow::anyhow!("TLS settings need a TLS feature, such as sync-native-tls"));
+                }
+            }
+        }
 
         for (name, value) in headers.iter() {
             session.header(HeaderName::from_bytes(name.as_ref()).unwrap(), value);
@@ -75,14 +94,61 @@ impl<'a> Request for AttoRequest<'a> {
             HttpMethod::Head => session.head(self.url()),
         };
 
-        let response = request.bytes(&self.request_body()).send()?;
-
-        if cfg!(feature = "fail-on-err") && response.status().as_u16() >= 400 {
-            return Err(anyhow!(
-                "Request failed with code {}\n{}",
+        let mut request = request.bytes(self.request_body()).try_prepare()?;
+        let mut attempt = 1;
+        let response = loop {
+            let response = match request.send() {
+                Ok(response) => response,
+                Err(e) if is_connection_error(&e) => {
+                    match self
+                        .bucket
+                        .retry_policy()
+                        .network_error_delay(attempt, &self.command.http_verb())
+                    {
+                        Some(delay) => {
+                            std::thread::sleep(delay);
+                            attempt += 1;
+                            continue;
+                        }
+                        None => return Err(e.into()),
+                    }
+                }
+                Err(e) => {
+                    return Err(match (self.bucket.request_timeout(), e.kind()) {
+                        (Some(timeout), attohttpc::ErrorKind::Io(io))
+                            if matches!(
+                                io.kind(),
+                                std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+                            ) =>
+                        {
+                            RequestTimeout { timeout }.into()
+                        }
+                        _ => anyhow::Error::from(e),
+                    })
+                }
+            };
+            let retry_after = response
+                .headers()
+                .get("Retry-After")
+                .and_then(|value| value.to_str().ok());
+            match self.bucket.retry_policy().retry_delay(
+                attempt,
+                &self.command.http_verb(),
                 response.status().as_u16(),
-                response.text()?
-            ));
+                retry_after,
+            ) {
+                Some(delay) => std::thread::sleep(delay),
+                None => break response,
+            }
+            attempt += 1;
+        };
+
+        if cfg!(feature = "fail-on-err")
+            && response.status().as_u16() >= 400
+            && !self.command.is_expected_status(response.status().as_u16())
+        {
+            let status = response.status().as_u16();
+            return Err(S3Error::from_response(status, &response.bytes()?).into());
         }
 
         Ok(response)
@@ -94,6 +160,7 @@ impl<'a> Request for AttoRequest<'a> {
         let headers = response.headers().clone();
         let etag_header = headers.get("ETag");
         let body = response.bytes()?;
+        check_content_length(&self.command, content_length(&headers), body.len() as u64)?;
         let mut body_vec = Vec::new();
         body_vec.extend_from_slice(&body[..]);
         if etag {
@@ -104,15 +171,26 @@ impl<'a> Request for AttoRequest<'a> {
         Ok((body_vec, status_code))
     }
 
-    fn response_data_to_writer<T: Write>(&self, writer: &mut T) -> Result<u16> {
+    fn response_data_to_writer<T: Write>(&self, writer: &mut T) -> Result<(u64, u16)> {
         let response = self.response()?;
 
         let status_code = response.status();
-        let stream = response.bytes()?;
-
-        writer.write_all(&stream)?;
+        let headers = response.headers().clone();
+        let mut verifier = self
+            .bucket
+            .checksum_algorithm()
+            .map(|_| ChecksumVerifier::new(&headers));
+        let written = response.write_to(&mut VerifiedWriter {
+            writer: &mut *writer,
+            verifier: verifier.as_mut(),
+        })?;
+        writer.flush()?;
+        check_content_length(&self.command, content_length(&headers), written)?;
+        if let Some(verifier) = verifier {
+            verifier.verify()?;
+        }
 
-        Ok(status_code.as_u16())
+        Ok((written, status_code.as_u16()))
     }
 
     fn response_header(&self) -> Result<(Self::HeaderMap, u16)> {
@@ -121,6 +199,56 @@ impl<'a> Request for AttoRequest<'a> {
         let headers = response.headers().clone();
         Ok((headers, status_code))
     }
+
+    fn response_data_with_headers(&self) -> Result<(Vec<u8>, Self::HeaderMap, u16)> {
+        let response = self.response()?;
+        let status_code = response.status().as_u16();
+        let headers = response.headers().clone();
+        let body = response.bytes()?;
+        check_content_length(&self.command, content_length(&headers), body.len() as u64)?;
+        Ok((body, headers, status_code))
+    }
+}
+
+/// Whether the request failed to connect, or lost its connection.
+fn is_connection_error(error: &attohttpc::Error) -> bool {
+    match error.kind() {
+        attohttpc::ErrorKind::Io(io) => matches!(
+            io.kind(),
+            std::io::ErrorKind::ConnectionRefused
+                | std::io::ErrorKind::ConnectionReset
+                | std::io::ErrorKind::ConnectionAborted
+                | std::io::ErrorKind::BrokenPipe
+                | std::io::ErrorKind::UnexpectedEof
+        ),
+        _ => false,
+    }
+}
+
+fn content_length(headers: &attohttpc::header::HeaderMap) -> Option<&str> {
+    headers
+        .get(attohttpc::header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+}
+
+/// Writes to `writer`, feeding `verifier` with what was written.
+struct VerifiedWriter<'a, T: Write> {
+    writer: &'a mut T,
+    verifier: Option<&'a mut ChecksumVerifier>,
+}
+
+impl<'a, T: Write> Write for VerifiedWriter<'a, T> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        if let Some(verifier) = &mut self.verifier {
+            verifier.update(&buf[..written]);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
 }
 
 impl<'a> AttoRequest<'a> {
@@ -4,11 +4,18 @@ use minidom::Element;
 use serde_xml_rs as serde_xml;
 use std::collections::HashMap;
 use std::mem;
+use std::time::Duration;
 
 use crate::bucket_ops::{BucketConfiguration, CreateBucketResponse};
-use crate::command::{Command, Multipart};
+use crate::checksum::{verify_checksum, ChecksumAlgorithm};
+use crate::command::{Command, Conditions, Multipart};
 use crate::creds::Credentials;
+use crate::post_policy::{self, PostPolicyCondition, PresignedPost};
 use crate::region::Region;
+use crate::retry::RetryPolicy;
+use crate::signing;
+use crate::sse::ServerSideEncryption;
+use crate::tls::Certificate;
 use std::str::FromStr;
 
 pub type Query = HashMap<String, String>;
@@ -23,9 +30,9 @@ use crate::surf_request::SurfRequest as RequestImpl;
 // use tokio::fs::File;
 
 #[cfg(feature = "with-async-std")]
-use futures::io::AsyncRead;
+use futures::io::{AsyncRead, AsyncWrite};
 #[cfg(feature = "with-tokio")]
-use tokio::io::AsyncRead;
+use tokio::io::{AsyncRead, AsyncWrite};
 
 #[cfg(feature = "sync")]
 use crate::blocking::AttoRequest as RequestImpl;
@@ -37,16 +44,27 @@ use std::io::Read;
 // use std::path::Path;
 
 use crate::request_trait::Request;
+use crate::serde_types::xml_escape;
 use crate::serde_types::{
-    BucketLocationResult, CompleteMultipartUploadData, HeadObjectResult,
-    InitiateMultipartUploadResponse, ListBucketResult, ListMultipartUploadsResult, Part,
+    BucketLocationResult, CompleteMultipartUploadData, CompleteMultipartUploadResult,
+    CopyObjectResult, DeleteError, DeleteObjectsData, DeleteResult, DeletedObject, GetObjectResult,
+    HeadObjectResult, InitiateMultipartUploadResponse, ListAllMyBucketsResult, ListBucketResult,
+    ListMultipartUploadsResult, ListPartsResult, Part, S3Error,
 };
 use anyhow::anyhow;
 use anyhow::Result;
-use http::header::HeaderName;
+use http::header::{
+    HeaderName, CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_ENCODING, CONTENT_LANGUAGE,
+    CONTENT_TYPE, EXPIRES,
+};
 use http::HeaderMap;
 
 pub const CHUNK_SIZE: usize = 8_388_608; // 8 Mebibytes, min is 5 (5_242_880);
+const DELETE_OBJECTS_MAX_KEYS: usize = 1000;
+const MIN_PART_SIZE: u64 = 5 << 20; // 5 Mebibytes, except for the last part
+const MAX_PART_NUMBER: u32 = 10000;
+const MAX_COPY_SIZE: u64 = 5 << 30; // 5 Gibibytes, the most one CopyObject can copy
+const COPY_PART_SIZE: u64 = 1 << 30; // 1 Gibibyte, up to 10 TiB in 10000 parts
 
 #[derive(Debug, PartialEq)]
 pub struct Tag {
@@ -78,6 +96,39 @@ impl Tag {
 ///
 /// let bucket = Bucket::new(bucket_name, region, credentials);
 /// ```
+///
+/// # Errors
+///
+/// Object operations returning a status code, such as `get_object` or
+/// `put_object`, return error responses along with their status code rather
+/// than failing, unless the `fail-on-err` feature is enabled: use
+/// `S3Error::check_response` to turn them into an `S3Error`.
+///
+/// # Google Cloud Storage
+///
+/// GCS serves the S3 API at `https://storage.googleapis.com`, signed with an
+/// [HMAC key](https://cloud.google.com/storage/docs/authentication/hmackeys)
+/// of a service account. It accepts any region name, and both
+/// virtual-hosted-style and path-style addressing, but checks that the
+/// signed `Host` is exactly the one requested, so give the endpoint without
+/// a port or trailing slash. Requests are signed with the SHA-256 of their
+/// payload, only presigned URLs use `UNSIGNED-PAYLOAD`, which GCS accepts.
+///
+/// ```no_run
+/// use s3::bucket::Bucket;
+/// use s3::creds::Credentials;
+/// use s3::region::Region;
+///
+/// let region = Region::Custom {
+///     region: "auto".to_owned(),
+///     endpoint: "https://storage.googleapis.com".to_owned(),
+/// };
+/// let credentials = Credentials::new(Some("GOOG1E..."), Some("secret"), None, None, None).unwrap();
+///
+/// let bucket = Bucket::new("rust-s3-test", region, credentials)
+///     .unwrap()
+///     .with_path_style();
+/// ```
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Bucket {
     pub name: String,
@@ -86,6 +137,264 @@ pub struct Bucket {
     pub extra_headers: HeaderMap,
     pub extra_query: Query,
     path_style: bool,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    checksum_algorithm: Option<ChecksumAlgorithm>,
+    accept_invalid_certs: bool,
+    root_certificates: Vec<Certificate>,
+    fips: bool,
+    dualstack: bool,
+    #[cfg(feature = "with-tokio")]
+    http_client: Option<HttpClient>,
+}
+
+/// A `reqwest::Client`, itself a handle shared by its clones. Clients can't
+/// be compared, buckets given one are equal if their other settings are.
+#[cfg(feature = "with-tokio")]
+#[derive(Clone, Debug)]
+struct HttpClient(reqwest::Client);
+
+#[cfg(feature = "with-tokio")]
+impl PartialEq for HttpClient {
+    fn eq(&self, _other: &HttpClient) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "with-tokio")]
+impl Eq for HttpClient {}
+
+/// Size of the `part_number`th part of a streamed upload: `CHUNK_SIZE`,
+/// doubled every 1000 parts up to 2 GiB.
+fn stream_part_size(part_number: u32) -> usize {
+    CHUNK_SIZE << ((part_number.saturating_sub(1) / 1000).min(8))
+}
+
+fn check_status(action: &str, code: u16, data: &[u8]) -> Result<()> {
+    if !(200..300).contains(&code) {
+        return Err(anyhow::Error::new(S3Error::from_response(code, data))
+            .context(format!("{} failed", action)));
+    }
+    Ok(())
+}
+
+/// Continuation token of the page following `page`, `None` when `page` is the
+/// last one.
+fn next_page_token(page: &ListBucketResult) -> Option<Option<String>> {
+    if page.is_truncated {
+        page.next_continuation_token.clone().map(Some)
+    } else {
+        None
+    }
+}
+
+/// Add the `<Deleted>` and `<Error>` entries of a `DeleteObjects` response to
+/// `result`. They may be interleaved, which serde can't deserialize.
+fn parse_delete_result(xml: &str, result: &mut DeleteResult) -> Result<()> {
+    let root = xml
+        .parse::<Element>()
+        .map_err(|e| anyhow!("Could not parse DeleteObjects result \n {}", e))?;
+    let text = |element: &Element, name: &str| {
+        element
+            .children()
+            .find(|child| child.name() == name)
+            .map(|child| child.text())
+    };
+    for entry in root.children() {
+        match entry.name() {
+            "Deleted" => result.deleted.push(DeletedObject {
+                key: text(entry, "Key").unwrap_or_default(),
+                version_id: text(entry, "VersionId"),
+            }),
+            "Error" => result.errors.push(DeleteError {
+                key: text(entry, "Key").unwrap_or_default(),
+                code: text(entry, "Code").unwrap_or_default(),
+                message: text(entry, "Message").unwrap_or_default(),
+            }),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Tags of a `GetObjectTagging` response, whatever namespace it uses.
+fn parse_tagging(xml: &str) -> Vec<Tag> {
+    let root = match xml.parse::<Element>() {
+        Ok(root) => root,
+        Err(_) => return Vec::new(),
+    };
+    let text = |element: &Element, name: &str| {
+        element
+            .children()
+            .find(|child| child.name() == name)
+            .map(|child| child.text())
+            .unwrap_or_default()
+    };
+    root.children()
+        .filter(|tag_set| tag_set.name() == "TagSet")
+        .flat_map(|tag_set| tag_set.children())
+        .filter(|tag| tag.name() == "Tag")
+        .map(|tag| Tag {
+            key: text(tag, "Key"),
+            value: text(tag, "Value"),
+        })
+        .collect()
+}
+
+/// Check `tags` against the limits of S3: up to 10 tags per object, with
+/// keys up to 128 characters long and values up to 256.
+fn validate_tags<S: AsRef<str>>(tags: &[(S, S)]) -> Result<()> {
+    if tags.len() > 10 {
+        return Err(anyhow!(
+            "Objects can have up to 10 tags, got {} instead",
+            tags.len()
+        ));
+    }
+    for (i, (key, value)) in tags.iter().enumerate() {
+        let (key, value) = (key.as_ref(), value.as_ref());
+        if tags[..i].iter().any(|(other, _)| other.as_ref() == key) {
+            return Err(anyhow!("Tag keys must be unique, got {:?} twice", key));
+        }
+        if key.is_empty() || key.chars().count() > 128 {
+            return Err(anyhow!(
+                "Tag keys must be 1 to 128 characters long, got {:?}",
+                key
+            ));
+        }
+        if value.chars().count() > 256 {
+            return Err(anyhow!(
+                "Tag values must be up to 256 characters long, got {:?} for {:?}",
+                value,
+                key
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// `x-amz-copy-source` of the object at `path` in `bucket`.
+fn copy_source(bucket: &str, path: &str) -> String {
+    let key = path.strip_prefix('/').unwrap_or(path);
+    format!("/{}/{}", bucket, signing::aws_uri_encode(key, false))
+}
+
+/// Headers giving a copy the metadata of the object `head` describes, for
+/// multipart copies, which start from none.
+fn copy_metadata(head: &HeadObjectResult) -> Result<HeaderMap> {
+    let mut metadata = HeaderMap::new();
+    let headers = [
+        (CONTENT_TYPE, &head.content_type),
+        (CACHE_CONTROL, &head.cache_control),
+        (CONTENT_DISPOSITION, &head.content_disposition),
+        (CONTENT_ENCODING, &head.content_encoding),
+        (CONTENT_LANGUAGE, &head.content_language),
+        (EXPIRES, &head.expires),
+    ];
+    for (name, value) in headers.iter() {
+        if let Some(value) = value {
+            metadata.insert(name.clone(), value.parse()?);
+        }
+    }
+    for (key, value) in head.metadata.iter().flatten() {
+        metadata.insert(
+            HeaderName::from_bytes(format!("x-amz-meta-{}", key).as_bytes())?,
+            value.parse()?,
+        );
+    }
+    Ok(metadata)
+}
+
+/// The part of `uploaded` with `part_number`, if `chunk` is its content.
+///
+/// Parts are compared by additional checksum when they have one, else by
+/// ETag when it is the MD5 of the part. ETags of SSE-KMS and SSE-C parts
+/// aren't, and those parts are only compared by size.
+fn uploaded_part(uploaded: &[Part], part_number: u32, chunk: &[u8]) -> Option<Part> {
+    let part = uploaded
+        .iter()
+        .find(|part| part.part_number == part_number)?;
+    let checksums: Vec<_> = ChecksumAlgorithm::ALL
+        .iter()
+        .filter_map(|algorithm| Some((algorithm, algorithm.part_checksum(part)?)))
+        .collect();
+    let etag = part.etag.trim_matches('"');
+    let matches = if !checksums.is_empty() {
+        checksums
+            .iter()
+            .all(|(algorithm, checksum)| algorithm.checksum(chunk) == *checksum)
+    } else if etag.len() == 32 && etag.bytes().all(|b| b.is_ascii_hexdigit()) {
+        etag.eq_ignore_ascii_case(&format!("{:x}", md5::compute(chunk)))
+    } else {
+        part.size == Some(chunk.len() as u64)
+    };
+    if matches {
+        Some(part.clone())
+    } else {
+        None
+    }
+}
+
+/// Fail uploads of streams announced to be `expected` bytes long that
+/// weren't, before storing the object.
+fn check_stream_size(expected: Option<u64>, read: u64) -> Result<()> {
+    match expected {
+        Some(expected) if expected != read => Err(anyhow!(
+            "Expected a stream of {} bytes, read {}",
+            expected,
+            read
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn validate_part_number(part_number: u32) -> Result<()> {
+    if !(1..=MAX_PART_NUMBER).contains(&part_number) {
+        return Err(anyhow!(
+            "Part numbers must be between 1 and {}, got {}",
+            MAX_PART_NUMBER,
+            part_number
+        ));
+    }
+    Ok(())
+}
+
+/// Reject parts S3 would refuse to complete an upload with: out of order,
+/// or smaller than 5 MiB but for the last one.
+fn validate_parts(parts: &[Part]) -> Result<()> {
+    if parts.is_empty() {
+        return Err(anyhow!("A multipart upload needs at least one part"));
+    }
+    let mut previous = 0;
+    for (i, part) in parts.iter().enumerate() {
+        validate_part_number(part.part_number)?;
+        if part.part_number <= previous {
+            return Err(anyhow!(
+                "Parts must be in ascending order of part number, got {} after {}",
+                part.part_number,
+                previous
+            ));
+        }
+        previous = part.part_number;
+        match part.size {
+            Some(size) if size < MIN_PART_SIZE && i + 1 < parts.len() => {
+                return Err(anyhow!(
+                    "Part {} is {} bytes, only the last part may be smaller than {}",
+                    part.part_number,
+                    size,
+                    MIN_PART_SIZE
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn validate_range(start: u64, end: Option<u64>) -> Result<()> {
+    match end {
+        Some(end) if end < start => Err(anyhow!("Range end {} is before its start {}", end, start)),
+        _ => Ok(()),
+    }
 }
 
 fn validate_expiry(expiry_secs: u32) -> Result<()> {
@@ -121,8 +430,53 @@ impl Bucket {
     /// println!("Presigned url: {}", url);
     /// ```
     pub fn presign_get<S: AsRef<str>>(&self, path: S, expiry_secs: u32) -> Result<String> {
+        self.presign_get_with(path, expiry_secs, None, None)
+    }
+
+    /// Get a presigned url for getting the object at `path`, with
+    /// `custom_queries` added to it, such as the `response-*` ones overriding
+    /// headers of the response, and signed for `custom_headers`. Clients must
+    /// then send these headers, with these values, for the URL to be valid.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use std::collections::HashMap;
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse().unwrap();
+    /// let credentials = Credentials::default().unwrap();
+    /// let bucket = Bucket::new(bucket_name, region, credentials).unwrap();
+    ///
+    /// let mut custom_queries = HashMap::new();
+    /// custom_queries.insert(
+    ///     "response-content-disposition".to_string(),
+    ///     "attachment; filename=\"report.pdf\"".to_string(),
+    /// );
+    /// let url = bucket
+    ///     .presign_get_with("/test.file", 86400, Some(custom_queries), None)
+    ///     .unwrap();
+    /// println!("Presigned url: {}", url);
+    /// ```
+    pub fn presign_get_with<S: AsRef<str>>(
+        &self,
+        path: S,
+        expiry_secs: u32,
+        custom_queries: Option<HashMap<String, String>>,
+        custom_headers: Option<HeaderMap>,
+    ) -> Result<String> {
         validate_expiry(expiry_secs)?;
-        let request = RequestImpl::new(self, path.as_ref(), Command::PresignGet { expiry_secs });
+        let request = RequestImpl::new(
+            self,
+            path.as_ref(),
+            Command::PresignGet {
+                expiry_secs,
+                custom_queries,
+                custom_headers,
+            },
+        );
         request.presigned()
     }
 
@@ -168,6 +522,160 @@ impl Bucket {
         );
         request.presigned()
     }
+
+    /// Get a presigned url for deleting object on a given path
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse().unwrap();
+    /// let credentials = Credentials::default().unwrap();
+    /// let bucket = Bucket::new(bucket_name, region, credentials).unwrap();
+    ///
+    /// let url = bucket.presign_delete("/test.file", 3600).unwrap();
+    /// println!("Presigned url: {}", url);
+    /// ```
+    pub fn presign_delete<S: AsRef<str>>(&self, path: S, expiry_secs: u32) -> Result<String> {
+        validate_expiry(expiry_secs)?;
+        let request = RequestImpl::new(self, path.as_ref(), Command::PresignDelete { expiry_secs });
+        request.presigned()
+    }
+
+    /// Get a presigned POST, for browsers to upload a file under
+    /// `key_prefix` with an HTML form, for `expiry_secs`.
+    ///
+    /// The file is stored at `key_prefix` followed by its name. The policy
+    /// of the POST restricts the uploads to the bucket, the key prefix, and
+    /// the other `conditions`, say on the size of the file. An `Eq` condition
+    /// on `key` stores the file at that exact key instead, which must then
+    /// start with `key_prefix`. Fails on such keys that don't, on conditions
+    /// on the fields signing the POST, such as `policy`, and on inverted
+    /// content length ranges.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use s3::post_policy::PostPolicyCondition;
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse().unwrap();
+    /// let credentials = Credentials::default().unwrap();
+    /// let bucket = Bucket::new(bucket_name, region, credentials).unwrap();
+    ///
+    /// let post = bucket
+    ///     .presign_post(
+    ///         "uploads/",
+    ///         3600,
+    ///         vec![PostPolicyCondition::ContentLengthRange { min: 1, max: 10 << 20 }],
+    ///     )
+    ///     .unwrap();
+    /// println!("<form action=\"{}\" method=\"post\" enctype=\"multipart/form-data\">", post.url);
+    /// for (name, value) in &post.fields {
+    ///     println!("<input type=\"hidden\" name=\"{}\" value=\"{}\">", name, value);
+    /// }
+    /// println!("<input type=\"file\" name=\"file\"></form>");
+    /// ```
+    pub fn presign_post(
+        &self,
+        key_prefix: &str,
+        expiry_secs: u32,
+        conditions: Vec<PostPolicyCondition>,
+    ) -> Result<PresignedPost> {
+        validate_expiry(expiry_secs)?;
+        for condition in &conditions {
+            condition.validate()?;
+            if let PostPolicyCondition::Eq { field, value } = condition {
+                if field.eq_ignore_ascii_case("key") && !value.starts_with(key_prefix) {
+                    return Err(anyhow!(
+                        "The key {:?} of a presigned POST must start with {:?}",
+                        value,
+                        key_prefix
+                    ));
+                }
+            }
+        }
+        let (access_key, secret_key) = match (self.access_key(), self.secret_key()) {
+            (Some(access_key), Some(secret_key)) => (access_key, secret_key),
+            _ => {
+                return Err(anyhow!(
+                    "Presigned POSTs need credentials to be signed with"
+                ))
+            }
+        };
+        let now = chrono::Utc::now();
+        let expiration = now + chrono::Duration::seconds(expiry_secs.into());
+
+        let mut fields = HashMap::new();
+        fields.insert("key".to_string(), format!("{}${{filename}}", key_prefix));
+        fields.insert(
+            "x-amz-algorithm".to_string(),
+            "AWS4-HMAC-SHA256".to_string(),
+        );
+        fields.insert(
+            "x-amz-credential".to_string(),
+            format!(
+                "{}/{}",
+                access_key,
+                signing::scope_string(&now, &self.region)
+            ),
+        );
+        fields.insert(
+            "x-amz-date".to_string(),
+            now.format(crate::LONG_DATE).to_string(),
+        );
+        if let Some(token) = self.security_token().or_else(|| self.session_token()) {
+            fields.insert("x-amz-security-token".to_string(), token.to_string());
+        }
+
+        let mut policy_conditions = vec![
+            PostPolicyCondition::Eq {
+                field: "bucket".to_string(),
+                value: self.name(),
+            },
+            PostPolicyCondition::StartsWith {
+                field: "key".to_string(),
+                prefix: key_prefix.to_string(),
+            },
+        ];
+        let mut signed_fields: Vec<_> =
+            fields.iter().filter(|(field, _)| *field != "key").collect();
+        signed_fields.sort();
+        for (field, value) in signed_fields {
+            policy_conditions.push(PostPolicyCondition::Eq {
+                field: field.clone(),
+                value: value.clone(),
+            });
+        }
+        for condition in conditions {
+            if let PostPolicyCondition::Eq { field, value } = &condition {
+                fields.insert(field.clone(), value.clone());
+            }
+            policy_conditions.push(condition);
+        }
+
+        let policy = base64::encode(post_policy::policy_document(
+            &expiration,
+            &policy_conditions,
+        ));
+        let signing_key = signing::signing_key(&now, &secret_key, &self.region, "s3")?;
+        fields.insert(
+            "x-amz-signature".to_string(),
+            signing::sign(&signing_key, &policy),
+        );
+        fields.insert("policy".to_string(), policy);
+
+        Ok(PresignedPost {
+            url: self.url(),
+            fields,
+        })
+    }
+
     /// Create a new `Bucket` and instantiate it
     ///
     /// ```no_run
@@ -274,6 +782,52 @@ impl Bucket {
         })
     }
 
+    /// List the buckets owned by the account of `credentials`, from the
+    /// endpoint of `region`.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use s3::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    ///
+    /// // Async variant with `tokio` or `async-std` features
+    /// let result = Bucket::list_buckets(region, credentials).await?;
+    ///
+    /// // `sync` fature will produce an identical method
+    /// #[cfg(feature = "sync")]
+    /// let result = Bucket::list_buckets(region, credentials)?;
+    ///
+    /// // Blocking variant, generated with `blocking` feature in combination
+    /// // with `tokio` or `async-std` features.
+    /// #[cfg(feature = "blocking")]
+    /// let result = Bucket::list_buckets_blocking(region, credentials)?;
+    ///
+    /// for bucket in result.buckets.bucket {
+    ///     println!("{} created on {}", bucket.name, bucket.creation_date);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::maybe_async]
+    pub async fn list_buckets(
+        region: Region,
+        credentials: Credentials,
+    ) -> Result<ListAllMyBucketsResult> {
+        // Addressed path-style, an unnamed bucket is the endpoint itself
+        let service = Bucket::new_with_path_style("", region, credentials)?;
+        let request = RequestImpl::new(&service, "", Command::ListBuckets);
+        let (data, code) = request.response_data(false).await?;
+        check_status("ListBuckets", code, &data)?;
+        serde_xml::from_reader(data.as_slice())
+            .map_err(|e| anyhow!("Could not deserialize result \n {}", e))
+    }
+
     /// Delete existing `Bucket`
     ///
     /// # Example
@@ -334,6 +888,15 @@ impl Bucket {
             extra_headers: HeaderMap::new(),
             extra_query: HashMap::new(),
             path_style: false,
+            retry_policy: RetryPolicy::never(),
+            request_timeout: None,
+            checksum_algorithm: None,
+            accept_invalid_certs: false,
+            root_certificates: Vec::new(),
+            fips: false,
+            dualstack: false,
+            #[cfg(feature = "with-tokio")]
+            http_client: None,
         })
     }
 
@@ -357,6 +920,15 @@ impl Bucket {
             extra_headers: HeaderMap::new(),
             extra_query: HashMap::new(),
             path_style: false,
+            retry_policy: RetryPolicy::never(),
+            request_timeout: None,
+            checksum_algorithm: None,
+            accept_invalid_certs: false,
+            root_certificates: Vec::new(),
+            fips: false,
+            dualstack: false,
+            #[cfg(feature = "with-tokio")]
+            http_client: None,
         })
     }
 
@@ -385,6 +957,15 @@ impl Bucket {
             extra_headers: HeaderMap::new(),
             extra_query: HashMap::new(),
             path_style: true,
+            retry_policy: RetryPolicy::never(),
+            request_timeout: None,
+            checksum_algorithm: None,
+            accept_invalid_certs: false,
+            root_certificates: Vec::new(),
+            fips: false,
+            dualstack: false,
+            #[cfg(feature = "with-tokio")]
+            http_client: None,
         })
     }
 
@@ -408,6 +989,15 @@ impl Bucket {
             extra_headers: HeaderMap::new(),
             extra_query: HashMap::new(),
             path_style: true,
+            retry_policy: RetryPolicy::never(),
+            request_timeout: None,
+            checksum_algorithm: None,
+            accept_invalid_certs: false,
+            root_certificates: Vec::new(),
+            fips: false,
+            dualstack: false,
+            #[cfg(feature = "with-tokio")]
+            http_client: None,
         })
     }
 
@@ -446,10 +1036,15 @@ impl Bucket {
     pub async fn get_object<S: AsRef<str>>(&self, path: S) -> Result<(Vec<u8>, u16)> {
         let command = Command::GetObject;
         let request = RequestImpl::new(self, path.as_ref(), command);
-        request.response_data(false).await
+        if self.checksum_algorithm.is_none() {
+            return request.response_data(false).await;
+        }
+        let (data, headers, status) = request.response_data_with_headers().await?;
+        verify_checksum(&data, &headers)?;
+        Ok((data, status))
     }
 
-    /// Gets torrent from an S3 path.
+    /// Gets file from an S3 path, along with its response headers.
     ///
     /// # Example:
     ///
@@ -467,32 +1062,45 @@ impl Bucket {
     /// let bucket = Bucket::new(bucket_name, region, credentials)?;
     ///
     /// // Async variant with `tokio` or `async-std` features
-    /// let (data, code) = bucket.get_object_torrent("/test.file").await?;
+    /// let (object, code) = bucket.get_object_with_headers("/test.file").await?;
+    /// println!("{:?} {:?}", object.etag, object.metadata.get("author"));
     ///
     /// // `sync` feature will produce an identical method
     /// #[cfg(feature = "sync")]
-    /// let (data, code) = bucket.get_object_torrent("/test.file")?;
+    /// let (object, code) = bucket.get_object_with_headers("/test.file")?;
     ///
     /// // Blocking variant, generated with `blocking` feature in combination
     /// // with `tokio` or `async-std` features.
     /// #[cfg(feature = "blocking")]
-    /// let (data, code) = bucket.get_object_torrent_blocking("/test.file")?;
+    /// let (object, code) = bucket.get_object_with_headers_blocking("/test.file")?;
     /// # Ok(())
     /// # }
     /// ```
     #[maybe_async::maybe_async]
-    pub async fn get_object_torrent<S: AsRef<str>>(&self, path: S) -> Result<(Vec<u8>, u16)> {
-        let command = Command::GetObjectTorrent;
+    pub async fn get_object_with_headers<S: AsRef<str>>(
+        &self,
+        path: S,
+    ) -> Result<(GetObjectResult, u16)> {
+        let command = Command::GetObject;
         let request = RequestImpl::new(self, path.as_ref(), command);
-        request.response_data(false).await
+        let (data, headers, status) = request.response_data_with_headers().await?;
+        if self.checksum_algorithm.is_some() {
+            verify_checksum(&data, &headers)?;
+        }
+        Ok((GetObjectResult::new(data, headers), status))
     }
 
-    /// Gets specified inclusive byte range of file from an S3 path.
+    /// Gets file from an S3 path, if it satisfies `conditions`.
+    ///
+    /// The status code is `304` with an empty body when `if_none_match` or
+    /// `if_modified_since` don't hold, and `412` when `if_match` or
+    /// `if_unmodified_since` don't, including with the `fail-on-err` feature.
     ///
     /// # Example:
     ///
     /// ```rust,no_run
     /// use s3::bucket::Bucket;
+    /// use s3::command::Conditions;
     /// use s3::creds::Credentials;
     /// use anyhow::Result;
     ///
@@ -503,39 +1111,39 @@ impl Bucket {
     /// let region = "us-east-1".parse()?;
     /// let credentials = Credentials::default()?;
     /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    /// let conditions = Conditions {
+    ///     if_none_match: Some("\"9b2cf535f27731c974343645a3985328\"".to_string()),
+    ///     ..Conditions::default()
+    /// };
     ///
     /// // Async variant with `tokio` or `async-std` features
-    /// let (data, code) = bucket.get_object_range("/test.file", 0, Some(31)).await?;
+    /// let (data, code) = bucket.get_object_if("/test.file", &conditions).await?;
     ///
     /// // `sync` feature will produce an identical method
     /// #[cfg(feature = "sync")]
-    /// let (data, code) = bucket.get_object_range("/test.file", 0, Some(31))?;
+    /// let (data, code) = bucket.get_object_if("/test.file", &conditions)?;
     ///
     /// // Blocking variant, generated with `blocking` feature in combination
     /// // with `tokio` or `async-std` features.
     /// #[cfg(feature = "blocking")]
-    /// let (data, code) = bucket.get_object_range_blocking("/test.file", 0, Some(31))?;
-    /// #
+    /// let (data, code) = bucket.get_object_if_blocking("/test.file", &conditions)?;
     /// # Ok(())
     /// # }
     /// ```
     #[maybe_async::maybe_async]
-    pub async fn get_object_range<S: AsRef<str>>(
+    pub async fn get_object_if<S: AsRef<str>>(
         &self,
         path: S,
-        start: u64,
-        end: Option<u64>,
+        conditions: &Conditions,
     ) -> Result<(Vec<u8>, u16)> {
-        if let Some(end) = end {
-            assert!(start < end);
-        }
-
-        let command = Command::GetObjectRange { start, end };
+        let command = Command::GetObjectIf {
+            conditions: conditions.clone(),
+        };
         let request = RequestImpl::new(self, path.as_ref(), command);
         request.response_data(false).await
     }
 
-    /// Stream file from S3 path to a local file, generic over T: Write.
+    /// Gets torrent from an S3 path.
     ///
     /// # Example:
     ///
@@ -543,7 +1151,6 @@ impl Bucket {
     /// use s3::bucket::Bucket;
     /// use s3::creds::Credentials;
     /// use anyhow::Result;
-    /// use std::fs::File;
     ///
     /// # #[tokio::main]
     /// # async fn main() -> Result<()> {
@@ -552,35 +1159,34 @@ impl Bucket {
     /// let region = "us-east-1".parse()?;
     /// let credentials = Credentials::default()?;
     /// let bucket = Bucket::new(bucket_name, region, credentials)?;
-    /// let mut output_file = File::create("output_file").expect("Unable to create file");
     ///
     /// // Async variant with `tokio` or `async-std` features
-    /// let status_code = bucket.get_object_stream("/test.file", &mut output_file).await?;
+    /// let (data, code) = bucket.get_object_torrent("/test.file").await?;
     ///
     /// // `sync` feature will produce an identical method
     /// #[cfg(feature = "sync")]
-    /// let status_code = bucket.get_object_stream("/test.file", &mut output_file)?;
+    /// let (data, code) = bucket.get_object_torrent("/test.file")?;
     ///
     /// // Blocking variant, generated with `blocking` feature in combination
     /// // with `tokio` or `async-std` features.
     /// #[cfg(feature = "blocking")]
-    /// let status_code = bucket.get_object_stream_blocking("/test.file", &mut output_file)?;
-    /// #
+    /// let (data, code) = bucket.get_object_torrent_blocking("/test.file")?;
     /// # Ok(())
     /// # }
     /// ```
     #[maybe_async::maybe_async]
-    pub async fn get_object_stream<T: std::io::Write + Send, S: AsRef<str>>(
-        &self,
-        path: S,
-        writer: &mut T,
-    ) -> Result<u16> {
-        let command = Command::GetObject;
+    pub async fn get_object_torrent<S: AsRef<str>>(&self, path: S) -> Result<(Vec<u8>, u16)> {
+        let command = Command::GetObjectTorrent;
         let request = RequestImpl::new(self, path.as_ref(), command);
-        request.response_data_to_writer(writer).await
+        request.response_data(false).await
     }
 
-    /// Stream file from local path to s3, generic over T: Write.
+    /// Gets specified inclusive byte range of file from an S3 path.
+    ///
+    /// S3 answers with `206 Partial Content`. Servers ignoring the `Range`
+    /// header, as some S3-compatible ones do, answer with `200 OK` and the
+    /// whole object instead, so check the status code before assuming only
+    /// the range was returned.
     ///
     /// # Example:
     ///
@@ -588,8 +1194,6 @@ impl Bucket {
     /// use s3::bucket::Bucket;
     /// use s3::creds::Credentials;
     /// use anyhow::Result;
-    /// use std::fs::File;
-    /// use std::io::Write;
     ///
     /// # #[tokio::main]
     /// # async fn main() -> Result<()> {
@@ -598,29 +1202,298 @@ impl Bucket {
     /// let region = "us-east-1".parse()?;
     /// let credentials = Credentials::default()?;
     /// let bucket = Bucket::new(bucket_name, region, credentials)?;
-    /// let path = "path";
-    /// let test: Vec<u8> = (0..1000).map(|_| 42).collect();
-    /// let mut file = File::create(path)?;
-    /// file.write_all(&test)?;
     ///
-    /// #[cfg(feature = "with-tokio")]
+    /// // Async variant with `tokio` or `async-std` features
+    /// let (data, code) = bucket.get_object_range("/test.file", 0, Some(31)).await?;
+    ///
+    /// // `sync` feature will produce an identical method
+    /// #[cfg(feature = "sync")]
+    /// let (data, code) = bucket.get_object_range("/test.file", 0, Some(31))?;
+    ///
+    /// // Blocking variant, generated with `blocking` feature in combination
+    /// // with `tokio` or `async-std` features.
+    /// #[cfg(feature = "blocking")]
+    /// let (data, code) = bucket.get_object_range_blocking("/test.file", 0, Some(31))?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::maybe_async]
+    pub async fn get_object_range<S: AsRef<str>>(
+        &self,
+        path: S,
+        start: u64,
+        end: Option<u64>,
+    ) -> Result<(Vec<u8>, u16)> {
+        validate_range(start, end)?;
+        let command = Command::GetObjectRange { start, end };
+        let request = RequestImpl::new(self, path.as_ref(), command);
+        request.response_data(false).await
+    }
+
+    /// Gets specified inclusive byte range of file from an S3 path, or from
+    /// `start` to the end of the file when `end` is `None`, along with the
+    /// response headers. S3 answers with `206 Partial Content`, and
+    /// `GetObjectResult::content_range` tells which bytes of the object were
+    /// returned, and its total size, say to resume an interrupted download.
+    /// A server ignoring the `Range` header answers with `200 OK`, the whole
+    /// object and no `content_range` instead.
+    ///
+    /// # Example:
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// // Async variant with `tokio` or `async-std` features
+    /// let (object, code) = bucket.get_object_range_with_headers("/test.file", 1024, None).await?;
+    ///
+    /// // `sync` feature will produce an identical method
+    /// #[cfg(feature = "sync")]
+    /// let (object, code) = bucket.get_object_range_with_headers("/test.file", 1024, None)?;
+    ///
+    /// // Blocking variant, generated with `blocking` feature in combination
+    /// // with `tokio` or `async-std` features.
+    /// #[cfg(feature = "blocking")]
+    /// let (object, code) = bucket.get_object_range_with_headers_blocking("/test.file", 1024, None)?;
+    ///
+    /// if let Some(range) = object.content_range {
+    ///     println!("Got bytes {} to {} of {:?}", range.start, range.end, range.total_size);
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::maybe_async]
+    pub async fn get_object_range_with_headers<S: AsRef<str>>(
+        &self,
+        path: S,
+        start: u64,
+        end: Option<u64>,
+    ) -> Result<(GetObjectResult, u16)> {
+        validate_range(start, end)?;
+        let command = Command::GetObjectRange { start, end };
+        let request = RequestImpl::new(self, path.as_ref(), command);
+        let (data, headers, status) = request.response_data_with_headers().await?;
+        Ok((GetObjectResult::new(data, headers), status))
+    }
+
+    /// Stream file from S3 path to a local file, generic over T: Write.
+    ///
+    /// The body is written chunk by chunk as it arrives, and the download is
+    /// aborted as soon as writing fails. Returns the status code, see
+    /// `get_object_stream_with_size` for the number of bytes written as well.
+    ///
+    /// When the connection drops mid-download, or the body turns out shorter
+    /// than its `Content-Length`, the error comes after the bytes received so
+    /// far were written: truncate or remove the output file then, or resume
+    /// from its length with `get_object_range`.
+    ///
+    /// # Example:
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// #[cfg(feature = "with-tokio")]
+    /// let mut output_file = tokio::fs::File::create("output_file").await?;
+    ///
+    /// #[cfg(feature = "with-async-std")]
+    /// let mut output_file = async_std::fs::File::create("output_file").await?;
+    /// // Async variant with `tokio` or `async-std` features
+    /// // Generic over futures::io::AsyncWrite|tokio::io::AsyncWrite + Unpin
+    /// let status_code = bucket.get_object_stream("/test.file", &mut output_file).await?;
+    ///
+    /// // `sync` feature will produce an identical method
+    /// #[cfg(feature = "sync")]
+    /// // Generic over std::io::Write
+    /// let status_code = bucket.get_object_stream("/test.file", &mut output_file)?;
+    ///
+    /// // Blocking variant, generated with `blocking` feature in combination
+    /// // with `tokio` or `async-std` features.
+    /// #[cfg(feature = "blocking")]
+    /// let status_code = bucket.get_object_stream_blocking("/test.file", &mut output_file)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::async_impl]
+    pub async fn get_object_stream<T: AsyncWrite + Send + Unpin, S: AsRef<str>>(
+        &self,
+        path: S,
+        writer: &mut T,
+    ) -> Result<u16> {
+        let (_written, code) = self.get_object_stream_with_size(path, writer).await?;
+        Ok(code)
+    }
+
+    #[maybe_async::sync_impl]
+    pub fn get_object_stream<T: std::io::Write + Send, S: AsRef<str>>(
+        &self,
+        path: S,
+        writer: &mut T,
+    ) -> Result<u16> {
+        let (_written, code) = self.get_object_stream_with_size(path, writer)?;
+        Ok(code)
+    }
+
+    /// Same as `get_object_stream`, also returning the number of bytes
+    /// written.
+    #[maybe_async::async_impl]
+    pub async fn get_object_stream_with_size<T: AsyncWrite + Send + Unpin, S: AsRef<str>>(
+        &self,
+        path: S,
+        writer: &mut T,
+    ) -> Result<(u64, u16)> {
+        let command = Command::GetObject;
+        let request = RequestImpl::new(self, path.as_ref(), command);
+        request.response_data_to_writer(writer).await
+    }
+
+    #[maybe_async::sync_impl]
+    pub fn get_object_stream_with_size<T: std::io::Write + Send, S: AsRef<str>>(
+        &self,
+        path: S,
+        writer: &mut T,
+    ) -> Result<(u64, u16)> {
+        let command = Command::GetObject;
+        let request = RequestImpl::new(self, path.as_ref(), command);
+        request.response_data_to_writer(writer)
+    }
+
+    /// Stream the object at `path` into `writer` chunk by chunk, returning
+    /// the number of bytes written and the status code.
+    ///
+    /// A connection dropped mid-download fails after the bytes received so
+    /// far were written, so truncate or remove the output file on error.
+    ///
+    /// # Example:
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # fn main() -> Result<()> {
+    /// let bucket = Bucket::new("rust-s3-test", "us-east-1".parse()?, Credentials::default()?)?;
+    /// let mut output_file = std::fs::File::create("output_file")?;
+    /// #[cfg(feature = "sync")]
+    /// let (written, status_code) = bucket.get_object_to_writer("/test.file", &mut output_file)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::sync_impl]
+    pub fn get_object_to_writer<W: std::io::Write + Send, S: AsRef<str>>(
+        &self,
+        path: S,
+        writer: &mut W,
+    ) -> Result<(u64, u16)> {
+        self.get_object_stream_with_size(path, writer)
+    }
+
+    /// Stream the object at `path` into `writer` chunk by chunk, returning
+    /// the number of bytes written and the status code. `writer` may be
+    /// borrowed, as in `&mut file`.
+    ///
+    /// A connection dropped mid-download fails after the bytes received so
+    /// far were written, so truncate or remove the output file on error.
+    ///
+    /// # Example:
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let bucket = Bucket::new("rust-s3-test", "us-east-1".parse()?, Credentials::default()?)?;
+    /// #[cfg(feature = "with-tokio")]
+    /// let output_file = tokio::fs::File::create("output_file").await?;
+    /// #[cfg(feature = "with-async-std")]
+    /// let output_file = async_std::fs::File::create("output_file").await?;
+    /// #[cfg(not(feature = "sync"))]
+    /// let (written, status_code) = bucket.get_object_stream_to("/test.file", output_file).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::async_impl]
+    pub async fn get_object_stream_to<W: AsyncWrite + Send + Unpin, S: AsRef<str>>(
+        &self,
+        path: S,
+        writer: W,
+    ) -> Result<(u64, u16)> {
+        let mut writer = writer;
+        self.get_object_stream_with_size(path, &mut writer).await
+    }
+
+    /// Stream file from local path to s3, generic over T: Write.
+    ///
+    /// Content of at least `CHUNK_SIZE` bytes is sent as a multipart upload,
+    /// one part at a time so that only a part is held in memory. Parts grow
+    /// from `CHUNK_SIZE` as the upload goes, so that the 10000 parts S3
+    /// allows cover objects up to its 5 TiB limit. If the upload fails, it
+    /// is aborted rather than leaving billed parts behind.
+    ///
+    /// Returns the ETag of the object and the status code.
+    ///
+    /// # Example:
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    /// use std::fs::File;
+    /// use std::io::Write;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    /// let path = "path";
+    /// let test: Vec<u8> = (0..1000).map(|_| 42).collect();
+    /// let mut file = File::create(path)?;
+    /// file.write_all(&test)?;
+    ///
+    /// #[cfg(feature = "with-tokio")]
     /// let mut path = tokio::fs::File::open(path).await?;
     ///
     /// #[cfg(feature = "with-async-std")]
     /// let mut path = async_std::fs::File::open(path).await?;
     /// // Async variant with `tokio` or `async-std` features
     /// // Generic over futures::io::AsyncRead|tokio::io::AsyncRead + Unpin
-    /// let status_code = bucket.put_object_stream(&mut path, "/path").await?;
+    /// let (etag, status_code) = bucket.put_object_stream(&mut path, "/path").await?;
     ///
     /// // `sync` feature will produce an identical method
     /// #[cfg(feature = "sync")]
     /// // Generic over std::io::Read
-    /// let status_code = bucket.put_object_stream(&mut path, "/path")?;
+    /// let (etag, status_code) = bucket.put_object_stream(&mut path, "/path")?;
     ///
     /// // Blocking variant, generated with `blocking` feature in combination
     /// // with `tokio` or `async-std` features.
     /// #[cfg(feature = "blocking")]
-    /// let status_code = bucket.put_object_stream_blocking(&mut path, "/path")?;
+    /// let (etag, status_code) = bucket.put_object_stream_blocking(&mut path, "/path")?;
     /// #
     /// # Ok(())
     /// # }
@@ -630,8 +1503,9 @@ impl Bucket {
         &self,
         reader: &mut R,
         s3_path: impl AsRef<str>,
-    ) -> Result<u16> {
-        self._put_object_stream(reader, s3_path.as_ref()).await
+    ) -> Result<(String, u16)> {
+        self._put_object_stream(reader, s3_path.as_ref(), None)
+            .await
     }
 
     #[maybe_async::sync_impl]
@@ -639,154 +1513,1101 @@ impl Bucket {
         &self,
         reader: &mut R,
         s3_path: impl AsRef<str>,
-    ) -> Result<u16> {
-        self._put_object_stream(reader, s3_path.as_ref())
+    ) -> Result<(String, u16)> {
+        self._put_object_stream(reader, s3_path.as_ref(), None)
     }
 
+    /// Same as `put_object_stream`, for a `reader` of exactly `size` bytes,
+    /// such as a file whose length is known. The upload fails, and is
+    /// aborted, rather than storing a truncated object if `reader` ends
+    /// early, or yields more than `size` bytes.
     #[maybe_async::async_impl]
-    async fn _put_object_stream<R: AsyncRead + Unpin>(
+    pub async fn put_object_stream_with_size<R: AsyncRead + Unpin>(
         &self,
         reader: &mut R,
-        s3_path: &str,
-    ) -> Result<u16> {
-        let command = Command::InitiateMultipartUpload;
-        let request = RequestImpl::new(self, &s3_path, command);
-        let (data, code) = request.response_data(false).await?;
-        let msg: InitiateMultipartUploadResponse =
-            serde_xml::from_str(std::str::from_utf8(data.as_slice())?)?;
-        let path = msg.key;
-        let upload_id = &msg.upload_id;
-
-        let mut part_number: u32 = 0;
-        let mut etags = Vec::new();
-        loop {
-            let chunk = crate::utils::read_chunk(reader).await?;
-
-            if chunk.len() < CHUNK_SIZE {
-                if part_number == 0 {
-                    // Files is not big enough for multipart upload, going with regular put_object
-                    self.abort_upload(&path, upload_id).await?;
-
-                    self.put_object(s3_path, chunk.as_slice()).await?;
-                    break;
-                } else {
-                    part_number += 1;
-                    let command = Command::PutObject {
-                        // part_number,
-                        content: &chunk,
-                        content_type: "application/octet-stream",
-                        multipart: Some(Multipart::new(part_number, upload_id)), // upload_id: &msg.upload_id,
-                    };
-                    let request = RequestImpl::new(self, &path, command);
-                    let (data, _code) = request.response_data(true).await?;
-                    let etag = std::str::from_utf8(data.as_slice())?;
-                    etags.push(etag.to_string());
-                    let inner_data = etags
-                        .clone()
-                        .into_iter()
-                        .enumerate()
-                        .map(|(i, x)| Part {
-                            etag: x,
-                            part_number: i as u32 + 1,
-                        })
-                        .collect::<Vec<Part>>();
-                    let data = CompleteMultipartUploadData { parts: inner_data };
-                    let complete = Command::CompleteMultipartUpload {
-                        upload_id: &msg.upload_id,
-                        data,
-                    };
-                    let complete_request = RequestImpl::new(self, &path, complete);
-                    let (_data, _code) = complete_request.response_data(false).await?;
-                    // let response = std::str::from_utf8(data.as_slice())?;
-                    break;
-                }
-            } else {
-                part_number += 1;
-                let command = Command::PutObject {
-                    // part_number,
-                    content: &chunk,
-                    content_type: "application/octet-stream",
-                    multipart: Some(Multipart::new(part_number, upload_id)), // upload_id: &msg.upload_id,
-                };
-                let request = RequestImpl::new(self, &path, command);
-                let (data, _code) = request.response_data(true).await?;
-                let etag = std::str::from_utf8(data.as_slice())?;
-                etags.push(etag.to_string());
-            }
-        }
-        Ok(code)
+        s3_path: impl AsRef<str>,
+        size: u64,
+    ) -> Result<(String, u16)> {
+        self._put_object_stream(reader, s3_path.as_ref(), Some(size))
+            .await
     }
 
     #[maybe_async::sync_impl]
-    fn _put_object_stream<R: Read>(&self, reader: &mut R, s3_path: &str) -> Result<u16> {
-        let command = Command::InitiateMultipartUpload;
-        let request = RequestImpl::new(self, &s3_path, command);
-        let (data, code) = request.response_data(false)?;
-        let msg: InitiateMultipartUploadResponse =
-            serde_xml::from_str(std::str::from_utf8(data.as_slice())?)?;
-
-        let path = msg.key;
-        let upload_id = &msg.upload_id;
-
-        let mut part_number: u32 = 0;
-        let mut etags = Vec::new();
-        loop {
-            let chunk = crate::utils::read_chunk(reader)?;
-
-            if chunk.len() < CHUNK_SIZE {
-                if part_number == 0 {
-                    // Files is not big enough for multipart upload, going with regular put_object
-                    self.abort_upload(&path, upload_id)?;
-
-                    self.put_object(s3_path, chunk.as_slice())?;
-                    break;
-                } else {
-                    part_number += 1;
-                    let command = Command::PutObject {
-                        // part_number,
-                        content: &chunk,
-                        content_type: "application/octet-stream",
-                        multipart: Some(Multipart::new(part_number, upload_id)), // upload_id: &msg.upload_id,
-                    };
-                    let request = RequestImpl::new(self, &path, command);
-                    let (data, _code) = request.response_data(true)?;
-                    let etag = std::str::from_utf8(data.as_slice())?;
-                    etags.push(etag.to_string());
-                    let inner_data = etags
-                        .into_iter()
-                        .enumerate()
-                        .map(|(i, x)| Part {
-                            etag: x,
-                            part_number: i as u32 + 1,
-                        })
-                        .collect::<Vec<Part>>();
-                    let data = CompleteMultipartUploadData { parts: inner_data };
-                    let complete = Command::CompleteMultipartUpload {
-                        upload_id: &msg.upload_id,
-                        data,
-                    };
-                    let complete_request = RequestImpl::new(self, &path, complete);
-                    let (_data, _code) = complete_request.response_data(false)?;
-                    // let response = std::str::from_utf8(data.as_slice())?;
-                    break;
-                }
-            } else {
-                part_number += 1;
-                let command = Command::PutObject {
-                    content: &chunk,
-                    content_type: "application/octet-stream",
-                    multipart: Some(Multipart::new(part_number, upload_id)),
-                };
-                let request = RequestImpl::new(self, &path, command);
-                let (data, _code) = request.response_data(true)?;
-                let etag = std::str::from_utf8(data.as_slice())?;
-                etags.push(etag.to_string());
-            }
-        }
-        Ok(code)
+    pub fn put_object_stream_with_size<R: Read>(
+        &self,
+        reader: &mut R,
+        s3_path: impl AsRef<str>,
+        size: u64,
+    ) -> Result<(String, u16)> {
+        self._put_object_stream(reader, s3_path.as_ref(), Some(size))
     }
 
-    /// Get Bucket location.
+    /// Resume a streamed multipart upload, started with
+    /// `initiate_multipart_upload`, that failed or was interrupted.
+    ///
+    /// `reader` is read from the start again. Chunks matching, by part number
+    /// and checksum or ETag, one of the `uploaded` parts, as returned by
+    /// `list_multipart_parts`, are not uploaded again. The ETags of parts
+    /// encrypted with SSE-KMS or SSE-C are not their MD5: without
+    /// `with_checksum`, their chunks are only compared by size, and must be
+    /// the same as when they were uploaded. Unlike
+    /// `put_object_stream`, the upload is not aborted when it fails, so that
+    /// it can be resumed again.
+    ///
+    /// # Example:
+    ///
+    /// ```rust,no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// #[cfg(feature = "with-tokio")]
+    /// let mut file = tokio::fs::File::open("path").await?;
+    ///
+    /// #[cfg(feature = "with-async-std")]
+    /// let mut file = async_std::fs::File::open("path").await?;
+    ///
+    /// // Async variant with `tokio` or `async-std` features
+    /// let upload = bucket.initiate_multipart_upload("/path").await?;
+    /// if bucket.put_object_stream_resume(&mut file, &upload, &[]).await.is_err() {
+    ///     let uploaded = bucket.list_multipart_parts("/path", &upload.upload_id).await?;
+    ///     #[cfg(feature = "with-tokio")]
+    ///     let mut file = tokio::fs::File::open("path").await?;
+    ///     #[cfg(feature = "with-async-std")]
+    ///     let mut file = async_std::fs::File::open("path").await?;
+    ///     bucket.put_object_stream_resume(&mut file, &upload, &uploaded).await?;
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::async_impl]
+    pub async fn put_object_stream_resume<R: AsyncRead + Unpin>(
+        &self,
+        reader: &mut R,
+        upload: &InitiateMultipartUploadResponse,
+        uploaded: &[Part],
+    ) -> Result<(String, u16)> {
+        let chunk = crate::utils::read_chunk(reader).await?;
+        self._upload_parts(reader, upload, chunk, uploaded, None)
+            .await
+    }
+
+    #[maybe_async::sync_impl]
+    pub fn put_object_stream_resume<R: Read>(
+        &self,
+        reader: &mut R,
+        upload: &InitiateMultipartUploadResponse,
+        uploaded: &[Part],
+    ) -> Result<(String, u16)> {
+        let chunk = crate::utils::read_chunk(reader)?;
+        self._upload_parts(reader, upload, chunk, uploaded, None)
+    }
+
+    #[maybe_async::async_impl]
+    async fn _put_object_stream<R: AsyncRead + Unpin>(
+        &self,
+        reader: &mut R,
+        s3_path: &str,
+        size: Option<u64>,
+    ) -> Result<(String, u16)> {
+        let chunk = crate::utils::read_chunk(reader).await?;
+        if chunk.len() < CHUNK_SIZE {
+            // Not big enough for a multipart upload, going with a regular put
+            check_stream_size(size, chunk.len() as u64)?;
+            return self._put_part(s3_path, &chunk, None).await;
+        }
+
+        let msg = self.initiate_multipart_upload(s3_path).await?;
+        let result = self._upload_parts(reader, &msg, chunk, &[], size).await;
+        if result.is_err() {
+            // Parts are billed until the upload is aborted, the original
+            // error is the one worth reporting
+            let _ = self.abort_upload(&msg.key, &msg.upload_id).await;
+        }
+        result
+    }
+
+    #[maybe_async::sync_impl]
+    fn _put_object_stream<R: Read>(
+        &self,
+        reader: &mut R,
+        s3_path: &str,
+        size: Option<u64>,
+    ) -> Result<(String, u16)> {
+        let chunk = crate::utils::read_chunk(reader)?;
+        if chunk.len() < CHUNK_SIZE {
+            // Not big enough for a multipart upload, going with a regular put
+            check_stream_size(size, chunk.len() as u64)?;
+            return self._put_part(s3_path, &chunk, None);
+        }
+
+        let msg = self.initiate_multipart_upload(s3_path)?;
+        let result = self._upload_parts(reader, &msg, chunk, &[], size);
+        if result.is_err() {
+            // Parts are billed until the upload is aborted, the original
+            // error is the one worth reporting
+            let _ = self.abort_upload(&msg.key, &msg.upload_id);
+        }
+        result
+    }
+
+    /// Upload `chunk` then the rest of `reader` as the parts of `msg`,
+    /// skipping the `uploaded` ones, and complete the upload.
+    #[maybe_async::async_impl]
+    async fn _upload_parts<R: AsyncRead + Unpin>(
+        &self,
+        reader: &mut R,
+        msg: &InitiateMultipartUploadResponse,
+        chunk: Vec<u8>,
+        uploaded: &[Part],
+        size: Option<u64>,
+    ) -> Result<(String, u16)> {
+        let mut parts = Vec::new();
+        let mut chunk = chunk;
+        let mut read = 0;
+        loop {
+            let part_number = parts.len() as u32 + 1;
+            let last = chunk.len() < stream_part_size(part_number);
+            read += chunk.len() as u64;
+            if !chunk.is_empty() {
+                match uploaded_part(uploaded, part_number, &chunk) {
+                    Some(part) => parts.push(part),
+                    None => {
+                        let multipart = Some(Multipart::new(part_number, &msg.upload_id));
+                        let (etag, _code) = self._put_part(&msg.key, &chunk, multipart).await?;
+                        let part = Part {
+                            etag,
+                            part_number,
+                            ..Part::default()
+                        };
+                        parts.push(match self.checksum_algorithm {
+                            Some(algorithm) => algorithm.with_part_checksum(part, &chunk),
+                            None => part,
+                        });
+                    }
+                }
+            }
+            if last {
+                check_stream_size(size, read)?;
+                return self._complete_upload(msg, parts).await;
+            }
+            chunk = crate::utils::read_chunk_with_size(reader, stream_part_size(part_number + 1))
+                .await?;
+        }
+    }
+
+    #[maybe_async::sync_impl]
+    fn _upload_parts<R: Read>(
+        &self,
+        reader: &mut R,
+        msg: &InitiateMultipartUploadResponse,
+        chunk: Vec<u8>,
+        uploaded: &[Part],
+        size: Option<u64>,
+    ) -> Result<(String, u16)> {
+        let mut parts = Vec::new();
+        let mut chunk = chunk;
+        let mut read = 0;
+        loop {
+            let part_number = parts.len() as u32 + 1;
+            let last = chunk.len() < stream_part_size(part_number);
+            read += chunk.len() as u64;
+            if !chunk.is_empty() {
+                match uploaded_part(uploaded, part_number, &chunk) {
+                    Some(part) => parts.push(part),
+                    None => {
+                        let multipart = Some(Multipart::new(part_number, &msg.upload_id));
+                        let (etag, _code) = self._put_part(&msg.key, &chunk, multipart)?;
+                        let part = Part {
+                            etag,
+                            part_number,
+                            ..Part::default()
+                        };
+                        parts.push(match self.checksum_algorithm {
+                            Some(algorithm) => algorithm.with_part_checksum(part, &chunk),
+                            None => part,
+                        });
+                    }
+                }
+            }
+            if last {
+                check_stream_size(size, read)?;
+                return self._complete_upload(msg, parts);
+            }
+            chunk = crate::utils::read_chunk_with_size(reader, stream_part_size(part_number + 1))?;
+        }
+    }
+
+    /// Start a multipart upload to `s3_path`, whose parts can then be
+    /// uploaded with `put_multipart_part`, or `put_object_stream_resume`.
+    #[maybe_async::maybe_async]
+    pub async fn initiate_multipart_upload(
+        &self,
+        s3_path: &str,
+    ) -> Result<InitiateMultipartUploadResponse> {
+        self._initiate_multipart_upload(s3_path, None).await
+    }
+
+    /// Start a multipart upload to `s3_path` of an object with `metadata`.
+    #[maybe_async::maybe_async]
+    async fn _initiate_multipart_upload(
+        &self,
+        s3_path: &str,
+        metadata: Option<HeaderMap>,
+    ) -> Result<InitiateMultipartUploadResponse> {
+        let command = Command::InitiateMultipartUpload { metadata };
+        let request = RequestImpl::new(self, s3_path, command);
+        let (data, code) = request.response_data(false).await?;
+        check_status("InitiateMultipartUpload", code, &data)?;
+        Ok(serde_xml::from_str(std::str::from_utf8(data.as_slice())?)?)
+    }
+
+    /// List the parts uploaded so far for the multipart upload `upload_id`
+    /// to `path`, to resume it with `put_object_stream_resume`.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    /// let upload_id = "ZDFjM2I0YmEtMzU3ZC00OTQ1LTlkNGUtMTgxZThjYzIwNjA2";
+    ///
+    /// // Async variant with `tokio` or `async-std` features
+    /// let parts = bucket.list_multipart_parts("/some/file.txt", upload_id).await?;
+    ///
+    /// // `sync` feature will produce an identical method
+    /// #[cfg(feature = "sync")]
+    /// let parts = bucket.list_multipart_parts("/some/file.txt", upload_id)?;
+    ///
+    /// // Blocking variant, generated with `blocking` feature in combination
+    /// // with `tokio` or `async-std` features.
+    /// #[cfg(feature = "blocking")]
+    /// let parts = bucket.list_multipart_parts_blocking("/some/file.txt", upload_id)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::maybe_async]
+    pub async fn list_multipart_parts(&self, path: &str, upload_id: &str) -> Result<Vec<Part>> {
+        let mut parts = Vec::new();
+        let mut part_number_marker = None;
+        loop {
+            let command = Command::ListParts {
+                upload_id,
+                part_number_marker,
+            };
+            let request = RequestImpl::new(self, path, command);
+            let (data, code) = request.response_data(false).await?;
+            check_status("ListParts", code, &data)?;
+            let page: ListPartsResult = serde_xml::from_reader(data.as_slice())
+                .map_err(|e| anyhow!("Could not deserialize result \n {}", e))?;
+            parts.extend(page.parts);
+            if !page.is_truncated || page.next_part_number_marker.is_none() {
+                return Ok(parts);
+            }
+            part_number_marker = page.next_part_number_marker;
+        }
+    }
+
+    /// Upload `content` as the part `part_number`, from 1 to 10000, of
+    /// `upload`, started with `initiate_multipart_upload`. Parts may be
+    /// uploaded in any order, from any process, and uploading a part number
+    /// again replaces it. All parts but the last must be of at least 5 MiB.
+    ///
+    /// Returns the `Part` to pass to `complete_multipart_upload`.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    /// let first = vec![0; 5 << 20];
+    /// let last = b"the end";
+    ///
+    /// // Async variant with `tokio` or `async-std` features
+    /// let upload = bucket.initiate_multipart_upload("/big.file").await?;
+    /// let parts = vec![
+    ///     bucket.put_multipart_part(&upload, 1, &first).await?,
+    ///     bucket.put_multipart_part(&upload, 2, last).await?,
+    /// ];
+    /// let (etag, code) = bucket.complete_multipart_upload(&upload, parts).await?;
+    ///
+    /// // `sync` feature will produce an identical method
+    /// #[cfg(feature = "sync")]
+    /// let part = bucket.put_multipart_part(&upload, 1, &first)?;
+    ///
+    /// // Blocking variant, generated with `blocking` feature in combination
+    /// // with `tokio` or `async-std` features.
+    /// #[cfg(feature = "blocking")]
+    /// let part = bucket.put_multipart_part_blocking(&upload, 1, &first)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::maybe_async]
+    pub async fn put_multipart_part(
+        &self,
+        upload: &InitiateMultipartUploadResponse,
+        part_number: u32,
+        content: &[u8],
+    ) -> Result<Part> {
+        validate_part_number(part_number)?;
+        let multipart = Some(Multipart::new(part_number, &upload.upload_id));
+        let (etag, _code) = self._put_part(&upload.key, content, multipart).await?;
+        let part = Part {
+            etag,
+            part_number,
+            size: Some(content.len() as u64),
+            ..Part::default()
+        };
+        Ok(match self.checksum_algorithm {
+            Some(algorithm) => algorithm.with_part_checksum(part, content),
+            None => part,
+        })
+    }
+
+    /// Assemble the `parts` of `upload`, in ascending order of part number,
+    /// into the uploaded object, returning its ETag. Parts may come from
+    /// `put_multipart_part` or `list_multipart_parts`. Use `abort_upload`
+    /// to give up on the upload instead.
+    #[maybe_async::maybe_async]
+    pub async fn complete_multipart_upload(
+        &self,
+        upload: &InitiateMultipartUploadResponse,
+        parts: Vec<Part>,
+    ) -> Result<(String, u16)> {
+        validate_parts(&parts)?;
+        self._complete_upload(upload, parts).await
+    }
+
+    /// Put `content` as a whole object, or as a part of a multipart upload,
+    /// returning its ETag.
+    #[maybe_async::maybe_async]
+    async fn _put_part(
+        &self,
+        path: &str,
+        content: &[u8],
+        multipart: Option<Multipart<'_>>,
+    ) -> Result<(String, u16)> {
+        let command = Command::PutObject {
+            content,
+            content_type: "application/octet-stream",
+            multipart,
+            custom_headers: None,
+        };
+        let request = RequestImpl::new(self, path, command);
+        let (data, code) = request.response_data(true).await?;
+        check_status("PutObject", code, &data)?;
+        Ok((String::from_utf8(data)?, code))
+    }
+
+    #[maybe_async::maybe_async]
+    async fn _complete_upload(
+        &self,
+        upload: &InitiateMultipartUploadResponse,
+        parts: Vec<Part>,
+    ) -> Result<(String, u16)> {
+        // Parts copied from an object have no checksum of their own
+        let expected_checksum = self
+            .checksum_algorithm
+            .and_then(|algorithm| Some((algorithm, algorithm.composite_checksum(&parts).ok()?)));
+        let complete = Command::CompleteMultipartUpload {
+            upload_id: &upload.upload_id,
+            data: CompleteMultipartUploadData { parts },
+        };
+        let request = RequestImpl::new(self, &upload.key, complete);
+        let (data, code) = request.response_data(false).await?;
+        check_status("CompleteMultipartUpload", code, &data)?;
+        // Errors may also come with a 200 status, and then fail to parse
+        let result: CompleteMultipartUploadResult =
+            serde_xml::from_str(std::str::from_utf8(data.as_slice())?)?;
+        if let Some((algorithm, expected)) = expected_checksum {
+            let returned = match algorithm {
+                ChecksumAlgorithm::Crc32c => result.checksum_crc32c.as_ref(),
+                ChecksumAlgorithm::Sha256 => result.checksum_sha256.as_ref(),
+            };
+            if let Some(returned) = returned {
+                if *returned != expected {
+                    return Err(anyhow!(
+                        "{} checksum mismatch of CompleteMultipartUpload, expected {}, got {}",
+                        algorithm.name(),
+                        expected,
+                        returned
+                    ));
+                }
+            }
+        }
+        Ok((result.etag, code))
+    }
+
+    /// Get Bucket location.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// // Async variant with `tokio` or `async-std` features
+    /// let (region, status_code) = bucket.location().await?;
+    ///
+    /// // `sync` feature will produce an identical method
+    /// #[cfg(feature = "sync")]
+    /// let (region, status_code) = bucket.location()?;
+    ///
+    /// // Blocking variant, generated with `blocking` feature in combination
+    /// // with `tokio` or `async-std` features.
+    /// #[cfg(feature = "blocking")]
+    /// let (region, status_code) = bucket.location_blocking()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::maybe_async]
+    pub async fn location(&self) -> Result<(Region, u16)> {
+        let request = RequestImpl::new(self, "?location", Command::GetBucketLocation);
+        let result = request.response_data(false).await?;
+        let region_string = String::from_utf8_lossy(&result.0);
+        let region = match serde_xml::from_reader(region_string.as_bytes()) {
+            Ok(r) => {
+                let location_result: BucketLocationResult = r;
+                location_result.region.parse()?
+            }
+            Err(e) => {
+                if result.1 == 200 {
+                    Region::Custom {
+                        region: "Custom".to_string(),
+                        endpoint: "".to_string(),
+                    }
+                } else {
+                    Region::Custom {
+                        region: format!("Error encountered : {}", e),
+                        endpoint: "".to_string(),
+                    }
+                }
+            }
+        };
+        Ok((region, result.1))
+    }
+
+    /// Copy the object at `from` to `to`, in the same bucket, without
+    /// downloading it. Returns the ETag of the copy.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// // Async variant with `tokio` or `async-std` features
+    /// let (etag, code) = bucket.copy_object("/test.file", "/copy of test.file").await?;
+    ///
+    /// // `sync` feature will produce an identical method
+    /// #[cfg(feature = "sync")]
+    /// let (etag, code) = bucket.copy_object("/test.file", "/copy of test.file")?;
+    ///
+    /// // Blocking variant, generated with `blocking` feature in combination
+    /// // with `tokio` or `async-std` features.
+    /// #[cfg(feature = "blocking")]
+    /// let (etag, code) = bucket.copy_object_blocking("/test.file", "/copy of test.file")?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::maybe_async]
+    pub async fn copy_object(&self, from: &str, to: &str) -> Result<(String, u16)> {
+        self.copy_object_to_bucket(from, self, to).await
+    }
+
+    /// Copy the object at `from` in this bucket to `dest_key` in
+    /// `dest_bucket`, server-side. Returns the ETag of the copy.
+    ///
+    /// A single request can copy objects of up to 5 GiB, larger ones are
+    /// copied with a multipart upload, 1 GiB at a time. The multipart upload
+    /// is aborted if any part fails, and is started with the content type,
+    /// `cache-control`-like headers and user metadata of `from`, which
+    /// multipart copies don't keep by themselves. Its tags, ACL and storage
+    /// class aren't kept. `dest_bucket`'s credentials must be allowed to read
+    /// `from`.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let region: s3::Region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new("rust-s3-test", region.clone(), credentials.clone())?;
+    /// let archive = Bucket::new("rust-s3-archive", region, credentials)?;
+    ///
+    /// // Async variant with `tokio` or `async-std` features
+    /// let (etag, code) = bucket.copy_object_to_bucket("/test.file", &archive, "/test.file").await?;
+    ///
+    /// // `sync` feature will produce an identical method
+    /// #[cfg(feature = "sync")]
+    /// let (etag, code) = bucket.copy_object_to_bucket("/test.file", &archive, "/test.file")?;
+    ///
+    /// // Blocking variant, generated with `blocking` feature in combination
+    /// // with `tokio` or `async-std` features.
+    /// #[cfg(feature = "blocking")]
+    /// let (etag, code) = bucket.copy_object_to_bucket_blocking("/test.file", &archive, "/test.file")?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::maybe_async]
+    pub async fn copy_object_to_bucket(
+        &self,
+        from: &str,
+        dest_bucket: &Bucket,
+        dest_key: &str,
+    ) -> Result<(String, u16)> {
+        self._copy_object(from, dest_bucket, dest_key, None, None)
+            .await
+    }
+
+    /// Same as `copy_object_to_bucket`, the copy getting `metadata` instead
+    /// of the metadata of `from`. `metadata` holds headers such as
+    /// `content-type`, `cache-control` or `x-amz-meta-*` ones, all of the
+    /// source's being dropped, including for objects larger than 5 GiB,
+    /// copied with a multipart upload started with `metadata`.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    /// use http::HeaderMap;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    /// let mut metadata = HeaderMap::new();
+    /// metadata.insert("content-type", "text/plain".parse()?);
+    ///
+    /// // Async variant with `tokio` or `async-std` features
+    /// let (etag, code) = bucket.copy_object_with_metadata("/test.file", &bucket, "/test.txt", metadata.clone()).await?;
+    ///
+    /// // `sync` feature will produce an identical method
+    /// #[cfg(feature = "sync")]
+    /// let (etag, code) = bucket.copy_object_with_metadata("/test.file", &bucket, "/test.txt", metadata.clone())?;
+    ///
+    /// // Blocking variant, generated with `blocking` feature in combination
+    /// // with `tokio` or `async-std` features.
+    /// #[cfg(feature = "blocking")]
+    /// let (etag, code) = bucket.copy_object_with_metadata_blocking("/test.file", &bucket, "/test.txt", metadata)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::maybe_async]
+    pub async fn copy_object_with_metadata(
+        &self,
+        from: &str,
+        dest_bucket: &Bucket,
+        dest_key: &str,
+        metadata: HeaderMap,
+    ) -> Result<(String, u16)> {
+        self._copy_object(from, dest_bucket, dest_key, Some(metadata), None)
+            .await
+    }
+
+    /// Copy an object to `dest_key` of `dest_bucket` if it satisfies
+    /// `conditions`, the status code is `412` with an empty ETag otherwise.
+    /// Only objects of up to 5 GiB can be copied this way.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::command::Conditions;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    /// let conditions = Conditions {
+    ///     if_match: Some("\"9b2cf535f27731c974343645a3985328\"".to_string()),
+    ///     ..Conditions::default()
+    /// };
+    ///
+    /// // Async variant with `tokio` or `async-std` features
+    /// let (etag, code) = bucket.copy_object_if("/test.file", &bucket, "/test.copy", &conditions).await?;
+    ///
+    /// // `sync` feature will produce an identical method
+    /// #[cfg(feature = "sync")]
+    /// let (etag, code) = bucket.copy_object_if("/test.file", &bucket, "/test.copy", &conditions)?;
+    ///
+    /// // Blocking variant, generated with `blocking` feature in combination
+    /// // with `tokio` or `async-std` features.
+    /// #[cfg(feature = "blocking")]
+    /// let (etag, code) = bucket.copy_object_if_blocking("/test.file", &bucket, "/test.copy", &conditions)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::maybe_async]
+    pub async fn copy_object_if(
+        &self,
+        from: &str,
+        dest_bucket: &Bucket,
+        dest_key: &str,
+        conditions: &Conditions,
+    ) -> Result<(String, u16)> {
+        self._copy_object(from, dest_bucket, dest_key, None, Some(conditions.clone()))
+            .await
+    }
+
+    #[maybe_async::maybe_async]
+    async fn _copy_object(
+        &self,
+        from: &str,
+        dest_bucket: &Bucket,
+        dest_key: &str,
+        metadata: Option<HeaderMap>,
+        conditions: Option<Conditions>,
+    ) -> Result<(String, u16)> {
+        let copy_source = copy_source(&self.name, from);
+        let (head, code) = self.head_object(from).await?;
+        check_status("HeadObject", code, &[])?;
+        let size = head.content_length.unwrap_or(0) as u64;
+
+        if size > MAX_COPY_SIZE && conditions.is_some() {
+            return Err(anyhow!(
+                "Can't copy {}, larger than 5 GiB, conditionally",
+                from
+            ));
+        }
+        if size <= MAX_COPY_SIZE {
+            let conditional = conditions.is_some();
+            let command = Command::CopyObject {
+                from: &copy_source,
+                metadata,
+                conditions,
+            };
+            let request = RequestImpl::new(dest_bucket, dest_key, command);
+            let (data, code) = request.response_data(false).await?;
+            if conditional && code == 412 {
+                return Ok((String::new(), code));
+            }
+            check_status("CopyObject", code, &data)?;
+            // Errors may also come with a 200 status, and then fail to parse
+            let result: CopyObjectResult =
+                serde_xml::from_str(std::str::from_utf8(data.as_slice())?)?;
+            return Ok((result.etag, code));
+        }
+
+        let metadata = match metadata {
+            Some(metadata) => metadata,
+            None => copy_metadata(&head)?,
+        };
+        let msg = dest_bucket
+            ._initiate_multipart_upload(dest_key, Some(metadata))
+            .await?;
+        let result = dest_bucket._copy_parts(&copy_source, &msg, size).await;
+        if result.is_err() {
+            let _ = dest_bucket.abort_upload(&msg.key, &msg.upload_id).await;
+        }
+        result
+    }
+
+    /// Copy the `size` bytes of `copy_source` as the parts of `msg`, and
+    /// complete the upload.
+    #[maybe_async::maybe_async]
+    async fn _copy_parts(
+        &self,
+        copy_source: &str,
+        msg: &InitiateMultipartUploadResponse,
+        size: u64,
+    ) -> Result<(String, u16)> {
+        let mut parts = Vec::new();
+        let mut start = 0;
+        while start < size {
+            let part_number = parts.len() as u32 + 1;
+            let end = size.min(start + COPY_PART_SIZE) - 1;
+            let command = Command::UploadPartCopy {
+                from: copy_source,
+                multipart: Multipart::new(part_number, &msg.upload_id),
+                start,
+                end,
+            };
+            let request = RequestImpl::new(self, &msg.key, command);
+            let (data, code) = request.response_data(false).await?;
+            check_status("UploadPartCopy", code, &data)?;
+            let result: CopyObjectResult =
+                serde_xml::from_str(std::str::from_utf8(data.as_slice())?)?;
+            parts.push(Part {
+                etag: result.etag,
+                part_number,
+                checksum_crc32c: result.checksum_crc32c,
+                checksum_sha256: result.checksum_sha256,
+                size: Some(end + 1 - start),
+            });
+            start = end + 1;
+        }
+        self._complete_upload(msg, parts).await
+    }
+
+    /// Delete many objects from an S3 bucket, using as few requests as
+    /// possible: one for every 1000 keys.
+    ///
+    /// Deleting some of the objects can fail while deleting the others
+    /// succeeds, `DeleteResult::errors` lists the keys that couldn't be
+    /// deleted along with the error code of each.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    /// let keys = vec!["/test.file".to_string(), "/test2.file".to_string()];
+    ///
+    /// // Async variant with `tokio` or `async-std` features
+    /// let result = bucket.delete_objects(&keys).await?;
+    ///
+    /// // `sync` feature will produce an identical method
+    /// #[cfg(feature = "sync")]
+    /// let result = bucket.delete_objects(&keys)?;
+    ///
+    /// // Blocking variant, generated with `blocking` feature in combination
+    /// // with `tokio` or `async-std` features.
+    /// #[cfg(feature = "blocking")]
+    /// let result = bucket.delete_objects_blocking(&keys)?;
+    ///
+    /// for error in result.errors {
+    ///     println!("{}: {}", error.key, error.code);
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::maybe_async]
+    pub async fn delete_objects(&self, keys: &[String]) -> Result<DeleteResult> {
+        let mut result = DeleteResult::default();
+        for keys in keys.chunks(DELETE_OBJECTS_MAX_KEYS) {
+            let data = DeleteObjectsData {
+                keys: keys
+                    .iter()
+                    .map(|key| key.strip_prefix('/').unwrap_or(key).to_string())
+                    .collect(),
+            };
+            let request = RequestImpl::new(self, "/", Command::DeleteObjects { data });
+            let (data, code) = request.response_data(false).await?;
+            check_status("DeleteObjects", code, &data)?;
+            parse_delete_result(&String::from_utf8_lossy(&data), &mut result)?;
+        }
+        Ok(result)
+    }
+
+    /// Delete file from an S3 path.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// // Async variant with `tokio` or `async-std` features
+    /// let (_, code) = bucket.delete_object("/test.file").await?;
+    ///
+    /// // `sync` feature will produce an identical method
+    /// #[cfg(feature = "sync")]
+    /// let (_, code) = bucket.delete_object("/test.file")?;
+    ///
+    /// // Blocking variant, generated with `blocking` feature in combination
+    /// // with `tokio` or `async-std` features.
+    /// #[cfg(feature = "blocking")]
+    /// let (_, code) = bucket.delete_object_blocking("/test.file")?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::maybe_async]
+    pub async fn delete_object<S: AsRef<str>>(&self, path: S) -> Result<(Vec<u8>, u16)> {
+        let command = Command::DeleteObject;
+        let request = RequestImpl::new(self, path.as_ref(), command);
+        request.response_data(false).await
+    }
+
+    /// Head object from S3.
+    ///
+    /// Returns the object's metadata without its body, such as its size,
+    /// content type, ETag, storage class and `x-amz-meta-*` user metadata.
+    /// A missing object is reported by a `404` status code rather than an
+    /// error, including with the `fail-on-err` feature, see also
+    /// `object_exists`.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// // Async variant with `tokio` or `async-std` features
+    /// let (head_object_result, code) = bucket.head_object("/test.png").await?;
+    ///
+    /// // `sync` feature will produce an identical method
+    /// #[cfg(feature = "sync")]
+    /// let (head_object_result, code) = bucket.head_object("/test.png")?;
+    ///
+    /// // Blocking variant, generated with `blocking` feature in combination
+    /// // with `tokio` or `async-std` features.
+    /// #[cfg(feature = "blocking")]
+    /// let (head_object_result, code) = bucket.head_object_blocking("/test.png")?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::maybe_async]
+    pub async fn head_object<S: AsRef<str>>(&self, path: S) -> Result<(HeadObjectResult, u16)> {
+        let command = Command::HeadObject;
+        let request = RequestImpl::new(self, path.as_ref(), command);
+        let (headers, status) = request.response_header().await?;
+        let header_object = HeadObjectResult::from(&headers);
+        Ok((header_object, status))
+    }
+
+    /// Head object from S3, encrypted at rest with `sse`, as `head_object`.
+    /// S3 refuses HEAD requests on objects encrypted with SSE-C without their
+    /// key.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use s3::sse::ServerSideEncryption;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    /// let sse = ServerSideEncryption::Customer { key: vec![42; 32] };
+    ///
+    /// // Async variant with `tokio` or `async-std` features
+    /// let (head_object_result, code) = bucket.head_object_with_sse("/test.png", &sse).await?;
+    ///
+    /// // `sync` feature will produce an identical method
+    /// #[cfg(feature = "sync")]
+    /// let (head_object_result, code) = bucket.head_object_with_sse("/test.png", &sse)?;
+    ///
+    /// // Blocking variant, generated with `blocking` feature in combination
+    /// // with `tokio` or `async-std` features.
+    /// #[cfg(feature = "blocking")]
+    /// let (head_object_result, code) = bucket.head_object_with_sse_blocking("/test.png", &sse)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::maybe_async]
+    pub async fn head_object_with_sse<S: AsRef<str>>(
+        &self,
+        path: S,
+        sse: &ServerSideEncryption,
+    ) -> Result<(HeadObjectResult, u16)> {
+        let command = Command::HeadObjectSse {
+            sse_headers: sse.read_headers()?,
+        };
+        let request = RequestImpl::new(self, path.as_ref(), command);
+        let (headers, status) = request.response_header().await?;
+        Ok((HeadObjectResult::from(&headers), status))
+    }
+
+    /// Check whether an object exists at an S3 path, using a HEAD request so
+    /// that its body is not downloaded.
+    ///
+    /// Only a `404` means the object doesn't exist, any other failure is an
+    /// error. In particular S3 answers `403` rather than `404` for a missing
+    /// object when the `s3:ListBucket` permission is missing, in which case
+    /// whether the object exists is unknown.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// // Async variant with `tokio` or `async-std` features
+    /// let exists = bucket.object_exists("/test.png").await?;
+    ///
+    /// // `sync` feature will produce an identical method
+    /// #[cfg(feature = "sync")]
+    /// let exists = bucket.object_exists("/test.png")?;
+    ///
+    /// // Blocking variant, generated with `blocking` feature in combination
+    /// // with `tokio` or `async-std` features.
+    /// #[cfg(feature = "blocking")]
+    /// let exists = bucket.object_exists_blocking("/test.png")?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::maybe_async]
+    pub async fn object_exists<S: AsRef<str>>(&self, path: S) -> Result<bool> {
+        let (_, status) = self.head_object(path).await?;
+        match status {
+            404 => Ok(false),
+            code => check_status("HeadObject", code, &[]).map(|()| true),
+        }
+    }
+
+    /// Put into an S3 bucket, with explicit content-type.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    /// let content = "I want to go to S3".as_bytes();
+    ///
+    /// // Async variant with `tokio` or `async-std` features
+    /// let (_, code) = bucket.put_object_with_content_type("/test.file", content, "text/plain").await?;
+    ///
+    /// // `sync` feature will produce an identical method
+    /// #[cfg(feature = "sync")]
+    /// let (_, code) = bucket.put_object_with_content_type("/test.file", content, "text/plain")?;
+    ///
+    /// // Blocking variant, generated with `blocking` feature in combination
+    /// // with `tokio` or `async-std` features.
+    /// #[cfg(feature = "blocking")]
+    /// let (_, code) = bucket.put_object_with_content_type_blocking("/test.file", content, "text/plain")?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[maybe_async::maybe_async]
+    pub async fn put_object_with_content_type<S: AsRef<str>>(
+        &self,
+        path: S,
+        content: &[u8],
+        content_type: &str,
+    ) -> Result<(Vec<u8>, u16)> {
+        let command = Command::PutObject {
+            content,
+            content_type,
+            multipart: None,
+            custom_headers: None,
+        };
+        let request = RequestImpl::new(self, path.as_ref(), command);
+        request.response_data(true).await
+    }
+
+    /// Put into an S3 bucket, sending and signing `headers` along with the
+    /// object, such as `x-amz-storage-class`, `x-amz-acl` or the
+    /// `x-amz-server-side-encryption-customer-*` headers of SSE-C.
+    ///
+    /// `Content-Type` defaults to `application/octet-stream` unless set in
+    /// `headers`. Headers computed for the request itself, such as `Host`,
+    /// `Content-Length` or `X-Amz-Date`, can't be overridden.
     ///
     /// # Example:
     ///
@@ -794,6 +2615,7 @@ impl Bucket {
     /// use s3::bucket::Bucket;
     /// use s3::creds::Credentials;
     /// use anyhow::Result;
+    /// use http::HeaderMap;
     ///
     /// # #[tokio::main]
     /// # async fn main() -> Result<()> {
@@ -802,56 +2624,52 @@ impl Bucket {
     /// let region = "us-east-1".parse()?;
     /// let credentials = Credentials::default()?;
     /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    /// let content = "I want to go to S3".as_bytes();
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert("x-amz-storage-class", "STANDARD_IA".parse()?);
     ///
     /// // Async variant with `tokio` or `async-std` features
-    /// let (region, status_code) = bucket.location().await?;
+    /// let (_, code) = bucket.put_object_with_headers("/test.file", content, headers.clone()).await?;
     ///
     /// // `sync` feature will produce an identical method
     /// #[cfg(feature = "sync")]
-    /// let (region, status_code) = bucket.location()?;
+    /// let (_, code) = bucket.put_object_with_headers("/test.file", content, headers.clone())?;
     ///
     /// // Blocking variant, generated with `blocking` feature in combination
     /// // with `tokio` or `async-std` features.
     /// #[cfg(feature = "blocking")]
-    /// let (region, status_code) = bucket.location_blocking()?;
+    /// let (_, code) = bucket.put_object_with_headers_blocking("/test.file", content, headers)?;
     /// #
     /// # Ok(())
     /// # }
     /// ```
     #[maybe_async::maybe_async]
-    pub async fn location(&self) -> Result<(Region, u16)> {
-        let request = RequestImpl::new(self, "?location", Command::GetBucketLocation);
-        let result = request.response_data(false).await?;
-        let region_string = String::from_utf8_lossy(&result.0);
-        let region = match serde_xml::from_reader(region_string.as_bytes()) {
-            Ok(r) => {
-                let location_result: BucketLocationResult = r;
-                location_result.region.parse()?
-            }
-            Err(e) => {
-                if result.1 == 200 {
-                    Region::Custom {
-                        region: "Custom".to_string(),
-                        endpoint: "".to_string(),
-                    }
-                } else {
-                    Region::Custom {
-                        region: format!("Error encountered : {}", e),
-                        endpoint: "".to_string(),
-                    }
-                }
-            }
+    pub async fn put_object_with_headers<S: AsRef<str>>(
+        &self,
+        path: S,
+        content: &[u8],
+        headers: HeaderMap,
+    ) -> Result<(Vec<u8>, u16)> {
+        let command = Command::PutObject {
+            content,
+            content_type: "application/octet-stream",
+            multipart: None,
+            custom_headers: Some(headers),
         };
-        Ok((region, result.1))
+        let request = RequestImpl::new(self, path.as_ref(), command);
+        request.response_data(true).await
     }
 
-    /// Delete file from an S3 path.
+    /// Put into an S3 bucket with `content_type`, encrypted at rest with
+    /// `sse`. Fails if the response doesn't confirm the object was encrypted that way, as with
+    /// S3-compatible servers ignoring encryption headers.
     ///
     /// # Example:
     ///
     /// ```no_run
     /// use s3::bucket::Bucket;
     /// use s3::creds::Credentials;
+    /// use s3::sse::ServerSideEncryption;
     /// use anyhow::Result;
     ///
     /// # #[tokio::main]
@@ -861,36 +2679,62 @@ impl Bucket {
     /// let region = "us-east-1".parse()?;
     /// let credentials = Credentials::default()?;
     /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    /// let content = "I want to go to S3".as_bytes();
+    /// let sse = ServerSideEncryption::AwsKms { key_id: Some("alias/my-key".to_string()) };
     ///
     /// // Async variant with `tokio` or `async-std` features
-    /// let (_, code) = bucket.delete_object("/test.file").await?;
+    /// let (_, code) = bucket.put_object_with_sse("/test.file", content, "text/plain", &sse).await?;
     ///
     /// // `sync` feature will produce an identical method
     /// #[cfg(feature = "sync")]
-    /// let (_, code) = bucket.delete_object("/test.file")?;
+    /// let (_, code) = bucket.put_object_with_sse("/test.file", content, "text/plain", &sse)?;
     ///
     /// // Blocking variant, generated with `blocking` feature in combination
     /// // with `tokio` or `async-std` features.
     /// #[cfg(feature = "blocking")]
-    /// let (_, code) = bucket.delete_object_blocking("/test.file")?;
+    /// let (_, code) = bucket.put_object_with_sse_blocking("/test.file", content, "text/plain", &sse)?;
     /// #
     /// # Ok(())
     /// # }
     /// ```
     #[maybe_async::maybe_async]
-    pub async fn delete_object<S: AsRef<str>>(&self, path: S) -> Result<(Vec<u8>, u16)> {
-        let command = Command::DeleteObject;
+    pub async fn put_object_with_sse<S: AsRef<str>>(
+        &self,
+        path: S,
+        content: &[u8],
+        content_type: &str,
+        sse: &ServerSideEncryption,
+    ) -> Result<(Vec<u8>, u16)> {
+        let command = Command::PutObject {
+            content,
+            content_type,
+            multipart: None,
+            custom_headers: Some(sse.headers()?),
+        };
         let request = RequestImpl::new(self, path.as_ref(), command);
-        request.response_data(false).await
+        let (data, headers, code) = request.response_data_with_headers().await?;
+        if !(200..300).contains(&code) {
+            return Ok((data, code));
+        }
+        sse.check_response(&headers)?;
+        // The ETag, as returned by `put_object`
+        let data = match headers.get("ETag") {
+            Some(etag) => etag.as_bytes().to_vec(),
+            None => data,
+        };
+        Ok((data, code))
     }
 
-    /// Head object from S3.
+    /// Gets file from an S3 path, encrypted at rest with `sse`. Objects
+    /// encrypted with SSE-C can only be read with the key they were put with,
+    /// other objects are read the same as with `get_object`.
     ///
     /// # Example:
     ///
     /// ```no_run
     /// use s3::bucket::Bucket;
     /// use s3::creds::Credentials;
+    /// use s3::sse::ServerSideEncryption;
     /// use anyhow::Result;
     ///
     /// # #[tokio::main]
@@ -900,37 +2744,53 @@ impl Bucket {
     /// let region = "us-east-1".parse()?;
     /// let credentials = Credentials::default()?;
     /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    /// let sse = ServerSideEncryption::Customer { key: vec![42; 32] };
     ///
     /// // Async variant with `tokio` or `async-std` features
-    /// let (head_object_result, code) = bucket.head_object("/test.png").await?;
+    /// let (data, code) = bucket.get_object_with_sse("/test.file", &sse).await?;
     ///
     /// // `sync` feature will produce an identical method
     /// #[cfg(feature = "sync")]
-    /// let (head_object_result, code) = bucket.head_object("/test.png")?;
+    /// let (data, code) = bucket.get_object_with_sse("/test.file", &sse)?;
     ///
     /// // Blocking variant, generated with `blocking` feature in combination
     /// // with `tokio` or `async-std` features.
     /// #[cfg(feature = "blocking")]
-    /// let (head_object_result, code) = bucket.head_object_blocking("/test.png")?;
+    /// let (data, code) = bucket.get_object_with_sse_blocking("/test.file", &sse)?;
     /// #
     /// # Ok(())
     /// # }
     /// ```
     #[maybe_async::maybe_async]
-    pub async fn head_object<S: AsRef<str>>(&self, path: S) -> Result<(HeadObjectResult, u16)> {
-        let command = Command::HeadObject;
+    pub async fn get_object_with_sse<S: AsRef<str>>(
+        &self,
+        path: S,
+        sse: &ServerSideEncryption,
+    ) -> Result<(Vec<u8>, u16)> {
+        let command = Command::GetObjectSse {
+            sse_headers: sse.read_headers()?,
+        };
         let request = RequestImpl::new(self, path.as_ref(), command);
-        let (headers, status) = request.response_header().await?;
-        let header_object = HeadObjectResult::from(&headers);
-        Ok((header_object, status))
+        if self.checksum_algorithm.is_none() {
+            return request.response_data(false).await;
+        }
+        let (data, headers, status) = request.response_data_with_headers().await?;
+        verify_checksum(&data, &headers)?;
+        Ok((data, status))
     }
 
-    /// Put into an S3 bucket, with explicit content-type.
+    /// Put into an S3 bucket, if the object currently stored at `path`
+    /// satisfies `conditions`, the status code is `412` otherwise.
+    ///
+    /// With `if_match` set to the ETag of the version an update is based on,
+    /// the update fails instead of overwriting concurrent changes. With
+    /// `if_none_match` set to `*`, it only creates objects that don't exist.
     ///
     /// # Example:
     ///
     /// ```no_run
     /// use s3::bucket::Bucket;
+    /// use s3::command::Conditions;
     /// use s3::creds::Credentials;
     /// use anyhow::Result;
     ///
@@ -942,36 +2802,35 @@ impl Bucket {
     /// let credentials = Credentials::default()?;
     /// let bucket = Bucket::new(bucket_name, region, credentials)?;
     /// let content = "I want to go to S3".as_bytes();
+    /// let conditions = Conditions {
+    ///     if_match: Some("\"9b2cf535f27731c974343645a3985328\"".to_string()),
+    ///     ..Conditions::default()
+    /// };
     ///
     /// // Async variant with `tokio` or `async-std` features
-    /// let (_, code) = bucket.put_object_with_content_type("/test.file", content, "text/plain").await?;
+    /// let (_, code) = bucket.put_object_if("/test.file", content, &conditions).await?;
     ///
     /// // `sync` feature will produce an identical method
     /// #[cfg(feature = "sync")]
-    /// let (_, code) = bucket.put_object_with_content_type("/test.file", content, "text/plain")?;
+    /// let (_, code) = bucket.put_object_if("/test.file", content, &conditions)?;
     ///
     /// // Blocking variant, generated with `blocking` feature in combination
     /// // with `tokio` or `async-std` features.
     /// #[cfg(feature = "blocking")]
-    /// let (_, code) = bucket.put_object_with_content_type_blocking("/test.file", content, "text/plain")?;
+    /// let (_, code) = bucket.put_object_if_blocking("/test.file", content, &conditions)?;
     /// #
     /// # Ok(())
     /// # }
     /// ```
     #[maybe_async::maybe_async]
-    pub async fn put_object_with_content_type<S: AsRef<str>>(
+    pub async fn put_object_if<S: AsRef<str>>(
         &self,
         path: S,
         content: &[u8],
-        content_type: &str,
+        conditions: &Conditions,
     ) -> Result<(Vec<u8>, u16)> {
-        let command = Command::PutObject {
-            content,
-            content_type,
-            multipart: None,
-        };
-        let request = RequestImpl::new(self, path.as_ref(), command);
-        request.response_data(true).await
+        self.put_object_with_headers(path, content, conditions.headers()?)
+            .await
     }
 
     /// Put into an S3 bucket.
@@ -1021,11 +2880,11 @@ impl Bucket {
         let mut s = String::new();
         let content = tags
             .iter()
-            .map(|&(ref name, ref value)| {
+            .map(|(name, value)| {
                 format!(
                     "<Tag><Key>{}</Key><Value>{}</Value></Tag>",
-                    name.as_ref(),
-                    value.as_ref()
+                    xml_escape(name.as_ref()),
+                    xml_escape(value.as_ref())
                 )
             })
             .fold(String::new(), |mut a, b| {
@@ -1038,7 +2897,11 @@ impl Bucket {
         s
     }
 
-    /// Tag an S3 object.
+    /// Tag an S3 object, replacing its existing tags.
+    ///
+    /// Fails without sending anything if `tags` exceed the limits of S3: up
+    /// to 10 tags, with unique keys up to 128 characters long and values up
+    /// to 256.
     ///
     /// # Example:
     ///
@@ -1076,7 +2939,8 @@ impl Bucket {
         path: &str,
         tags: &[(S, S)],
     ) -> Result<(Vec<u8>, u16)> {
-        let content = self._tags_xml(&tags);
+        validate_tags(tags)?;
+        let content = self._tags_xml(tags);
         let command = Command::PutObjectTagging { tags: &content };
         let request = RequestImpl::new(self, path, command);
         request.response_data(false).await
@@ -1159,33 +3023,11 @@ impl Bucket {
         let request = RequestImpl::new(self, path.as_ref(), command);
         let result = request.response_data(false).await?;
 
-        let mut tags = Vec::new();
-
-        if result.1 == 200 {
-            let result_string = String::from_utf8_lossy(&result.0);
-            let ns = "http://s3.amazonaws.com/doc/2006-03-01/";
-            if let Ok(tagging) = result_string.parse::<Element>() {
-                for tag_set in tagging.children() {
-                    if tag_set.is("TagSet", ns) {
-                        for tag in tag_set.children() {
-                            if tag.is("Tag", ns) {
-                                let key = if let Some(element) = tag.get_child("Key", ns) {
-                                    element.text()
-                                } else {
-                                    "Could not parse Key from Tag".to_string()
-                                };
-                                let value = if let Some(element) = tag.get_child("Values", ns) {
-                                    element.text()
-                                } else {
-                                    "Could not parse Values from Tag".to_string()
-                                };
-                                tags.push(Tag { key, value });
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        let tags = if result.1 == 200 {
+            parse_tagging(&String::from_utf8_lossy(&result.0))
+        } else {
+            Vec::new()
+        };
 
         Ok((tags, result.1))
     }
@@ -1208,9 +3050,9 @@ impl Bucket {
         };
         let request = RequestImpl::new(self, "/", command);
         let (response, status_code) = request.response_data(false).await?;
-        return serde_xml::from_reader(response.as_slice())
+        serde_xml::from_reader(response.as_slice())
             .map(|list_bucket_result| (list_bucket_result, status_code))
-            .map_err(|e| anyhow!("Could not deserialize result \n {}", e));
+            .map_err(|e| anyhow!("Could not deserialize result \n {}", e))
     }
 
     /// List the contents of an S3 bucket.
@@ -1275,6 +3117,137 @@ impl Bucket {
         Ok(results)
     }
 
+    /// Lazily list the contents of an S3 bucket, one page at a time.
+    ///
+    /// Unlike `list`, which collects every page before returning, the next
+    /// page is only requested once the previous one has been consumed,
+    /// following `NextContinuationToken` until the listing is no longer
+    /// truncated. The stream ends after the first error.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    /// use futures::StreamExt;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    ///
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// let pages = bucket.list_page_stream("/".to_string(), Some("/".to_string()));
+    /// futures::pin_mut!(pages);
+    /// while let Some(page) = pages.next().await {
+    ///     for object in page?.contents {
+    ///         println!("{}", object.key);
+    ///     }
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(any(feature = "with-tokio", feature = "with-async-std"))]
+    pub fn list_page_stream(
+        &self,
+        prefix: String,
+        delimiter: Option<String>,
+    ) -> impl futures::Stream<Item = Result<ListBucketResult>> + '_ {
+        self.list_page_stream_with_max_keys(prefix, delimiter, None)
+    }
+
+    /// Same as `list_page_stream`, with pages of at most `max_keys` objects
+    /// and common prefixes, 1000 being the most S3 returns.
+    #[cfg(any(feature = "with-tokio", feature = "with-async-std"))]
+    pub fn list_page_stream_with_max_keys(
+        &self,
+        prefix: String,
+        delimiter: Option<String>,
+        max_keys: Option<usize>,
+    ) -> impl futures::Stream<Item = Result<ListBucketResult>> + '_ {
+        // `None` once the last page has been returned, `Some(token)` otherwise
+        futures::stream::unfold(Some(None), move |continuation_token| {
+            let prefix = prefix.clone();
+            let delimiter = delimiter.clone();
+            async move {
+                let continuation_token = continuation_token?;
+                match self
+                    .list_page(prefix, delimiter, continuation_token, None, max_keys)
+                    .await
+                {
+                    Ok((list_bucket_result, _)) => {
+                        let next = next_page_token(&list_bucket_result);
+                        Some((Ok(list_bucket_result), next))
+                    }
+                    Err(e) => Some((Err(e), None)),
+                }
+            }
+        })
+    }
+
+    /// Lazily list the contents of an S3 bucket, one page at a time.
+    ///
+    /// Iterator counterpart of the async `list_page_stream`: each call to
+    /// `next` requests the following page, until the listing is no longer
+    /// truncated. Iteration ends after the first error.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use anyhow::Result;
+    ///
+    /// # fn main() -> Result<()> {
+    /// let bucket_name = "rust-s3-test";
+    /// let region = "us-east-1".parse()?;
+    /// let credentials = Credentials::default()?;
+    /// let bucket = Bucket::new(bucket_name, region, credentials)?;
+    ///
+    /// for page in bucket.list_page_stream("/".to_string(), Some("/".to_string())) {
+    ///     for object in page?.contents {
+    ///         println!("{}", object.key);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "sync")]
+    pub fn list_page_stream(
+        &self,
+        prefix: String,
+        delimiter: Option<String>,
+    ) -> impl Iterator<Item = Result<ListBucketResult>> + '_ {
+        self.list_page_stream_with_max_keys(prefix, delimiter, None)
+    }
+
+    /// Same as `list_page_stream`, with pages of at most `max_keys` objects
+    /// and common prefixes, 1000 being the most S3 returns.
+    #[cfg(feature = "sync")]
+    pub fn list_page_stream_with_max_keys(
+        &self,
+        prefix: String,
+        delimiter: Option<String>,
+        max_keys: Option<usize>,
+    ) -> impl Iterator<Item = Result<ListBucketResult>> + '_ {
+        // `None` once the last page has been returned, `Some(token)` otherwise
+        let mut continuation_token = Some(None);
+        std::iter::from_fn(move || {
+            let token = continuation_token.take()?;
+            match self.list_page(prefix.clone(), delimiter.clone(), token, None, max_keys) {
+                Ok((list_bucket_result, _)) => {
+                    continuation_token = next_page_token(&list_bucket_result);
+                    Some(Ok(list_bucket_result))
+                }
+                Err(e) => Some(Err(e)),
+            }
+        })
+    }
+
     #[maybe_async::maybe_async]
     pub async fn list_multiparts_uploads_page(
         &self,
@@ -1291,9 +3264,9 @@ impl Bucket {
         };
         let request = RequestImpl::new(self, "/", command);
         let (response, status_code) = request.response_data(false).await?;
-        return serde_xml::from_reader(response.as_slice())
+        serde_xml::from_reader(response.as_slice())
             .map(|list_bucket_result| (list_bucket_result, status_code))
-            .map_err(|e| anyhow!("Could not deserialize result \n {}", e));
+            .map_err(|e| anyhow!("Could not deserialize result \n {}", e))
     }
 
     /// List the ongoing multipart uploads of an S3 bucket. This may be useful to cleanup failed
@@ -1431,9 +3404,261 @@ impl Bucket {
         self.path_style = true;
     }
 
-    /// Configure bucket to use subdomain style urls and headers \[default\]
-    pub fn set_subdomain_style(&mut self) {
-        self.path_style = false;
+    /// Address the bucket path-style, as in `https://host/bucket/key`, instead
+    /// of virtual-hosted-style, as in `https://bucket.host/key` \[default\].
+    ///
+    /// Virtual-hosted-style requires the bucket name to resolve as a
+    /// subdomain of the endpoint. That is the case on AWS, but usually not
+    /// for a `Region::Custom` endpoint, such as a MinIO server or an on-prem
+    /// gateway reached by IP address, which then needs path-style. Either
+    /// way the request is signed for the host and path it is sent to, the
+    /// custom endpoint's port included.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use s3::region::Region;
+    ///
+    /// let region = Region::Custom {
+    ///     region: "us-east-1".to_owned(),
+    ///     endpoint: "http://localhost:9000".to_owned(),
+    /// };
+    /// let credentials = Credentials::default().unwrap();
+    ///
+    /// let bucket = Bucket::new("rust-s3-test", region, credentials)
+    ///     .unwrap()
+    ///     .with_path_style();
+    /// assert_eq!(bucket.url(), "http://localhost:9000/rust-s3-test");
+    /// ```
+    pub fn with_path_style(mut self) -> Bucket {
+        self.path_style = true;
+        self
+    }
+
+    /// Configure bucket to use subdomain style urls and headers \[default\]
+    pub fn set_subdomain_style(&mut self) {
+        self.path_style = false;
+    }
+
+    /// Address the bucket virtual-hosted-style \[default\], undoing
+    /// `with_path_style`.
+    pub fn with_subdomain_style(mut self) -> Bucket {
+        self.path_style = false;
+        self
+    }
+
+    /// Retry requests failing with a 5xx or 429 status according to `policy`,
+    /// see [`RetryPolicy`](../retry/struct.RetryPolicy.html). Requests are
+    /// sent once by default, `RetryPolicy::default()` makes up to 3 attempts.
+    pub fn with_retries(mut self, policy: RetryPolicy) -> Bucket {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Get the policy used to retry failed requests.
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Fail requests that take longer than `timeout` to connect, or to
+    /// complete, with a [`RequestTimeout`](../request_trait/struct.RequestTimeout.html)
+    /// error. Requests don't time out by default.
+    ///
+    /// The timeout applies to every request made through the bucket. To give
+    /// known slow operations, such as completing a large multipart upload,
+    /// more time, call them on a clone with a longer timeout.
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use std::time::Duration;
+    ///
+    /// let region = "us-east-1".parse().unwrap();
+    /// let credentials = Credentials::default().unwrap();
+    /// let bucket = Bucket::new("rust-s3-test", region, credentials)
+    ///     .unwrap()
+    ///     .with_request_timeout(Duration::from_secs(30));
+    /// let patient_bucket = bucket.clone().with_request_timeout(Duration::from_secs(300));
+    /// ```
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Bucket {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Get the timeout of requests, if any.
+    pub fn request_timeout(&self) -> Option<Duration> {
+        self.request_timeout
+    }
+
+    /// Send requests with `client`, rather than with a client built for each
+    /// request, to pool connections across requests and buckets.
+    ///
+    /// The client keeps its own settings, such as proxies, TLS and redirect
+    /// policy. Requests fail if the bucket has TLS settings of its own, they
+    /// don't apply to the client. The request timeout of the bucket, if any,
+    /// applies on top of the timeouts of the client, to the whole request.
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use std::time::Duration;
+    ///
+    /// let client = reqwest::Client::builder()
+    ///     .pool_max_idle_per_host(32)
+    ///     .build()
+    ///     .unwrap();
+    /// let region: s3::Region = "us-east-1".parse().unwrap();
+    /// let credentials = Credentials::default().unwrap();
+    /// let images = Bucket::new("rust-s3-images", region.clone(), credentials.clone())
+    ///     .unwrap()
+    ///     .with_http_client(client.clone());
+    /// let thumbnails = Bucket::new("rust-s3-thumbnails", region, credentials)
+    ///     .unwrap()
+    ///     .with_http_client(client)
+    ///     .with_request_timeout(Duration::from_secs(10));
+    /// ```
+    #[cfg(feature = "with-tokio")]
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Bucket {
+        self.http_client = Some(HttpClient(client));
+        self
+    }
+
+    /// Get the client requests are sent with, if set by `with_http_client`.
+    #[cfg(feature = "with-tokio")]
+    pub fn http_client(&self) -> Option<&reqwest::Client> {
+        self.http_client.as_ref().map(|client| &client.0)
+    }
+
+    /// Send a checksum of the content of uploads, computed with `algorithm`,
+    /// and check downloaded objects against the checksum S3 returns.
+    ///
+    /// Multipart uploads send the checksum of every part, and check that of
+    /// the whole upload once completed. Objects uploaded in parts can't be
+    /// checked once downloaded, S3 only knows the checksums of their parts.
+    ///
+    /// Whole objects are checked by `get_object`, `get_object_with_headers`,
+    /// `get_object_with_sse` and `get_object_stream`, the latter failing once
+    /// the object was written if it doesn't match. Ranges and conditional
+    /// reads aren't checked.
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::checksum::ChecksumAlgorithm;
+    /// use s3::creds::Credentials;
+    ///
+    /// let region = "us-east-1".parse().unwrap();
+    /// let credentials = Credentials::default().unwrap();
+    /// let bucket = Bucket::new("rust-s3-test", region, credentials)
+    ///     .unwrap()
+    ///     .with_checksum(ChecksumAlgorithm::Crc32c);
+    /// ```
+    pub fn with_checksum(mut self, algorithm: ChecksumAlgorithm) -> Bucket {
+        self.checksum_algorithm = Some(algorithm);
+        self
+    }
+
+    /// Get the algorithm of the checksums sent with uploads, if any.
+    pub fn checksum_algorithm(&self) -> Option<ChecksumAlgorithm> {
+        self.checksum_algorithm
+    }
+
+    /// Accept any TLS certificate from the endpoint, including self-signed
+    /// and expired ones, when `accept_invalid_certs` is `true`.
+    ///
+    /// # Danger
+    ///
+    /// Anyone able to intercept the connections can then read and tamper
+    /// with the requests, credentials included. Fine for a development
+    /// server, otherwise trust the certificate authority of the endpoint
+    /// with `with_extra_root_certificate` instead.
+    ///
+    /// Not supported with the `with-async-std` feature, whose requests then
+    /// fail.
+    pub fn with_danger_accept_invalid_certs(mut self, accept_invalid_certs: bool) -> Bucket {
+        self.accept_invalid_certs = accept_invalid_certs;
+        self
+    }
+
+    /// Whether any TLS certificate is accepted from the endpoint, see
+    /// `with_danger_accept_invalid_certs`.
+    pub fn danger_accept_invalid_certs(&self) -> bool {
+        self.accept_invalid_certs
+    }
+
+    /// Trust `certificate` as a root certificate, on top of the system ones,
+    /// for endpoints using a private certificate authority.
+    ///
+    /// Not supported with the `with-async-std` feature, whose requests then
+    /// fail.
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    /// use s3::region::Region;
+    /// use s3::tls::Certificate;
+    ///
+    /// let region = Region::Custom {
+    ///     region: "us-east-1".to_owned(),
+    ///     endpoint: "https://minio.internal:9000".to_owned(),
+    /// };
+    /// let credentials = Credentials::default().unwrap();
+    /// let certificate = Certificate::from_pem(&std::fs::read("ca.pem").unwrap()).unwrap();
+    /// let bucket = Bucket::new_with_path_style("rust-s3-test", region, credentials)
+    ///     .unwrap()
+    ///     .with_extra_root_certificate(certificate);
+    /// ```
+    pub fn with_extra_root_certificate(mut self, certificate: Certificate) -> Bucket {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    /// Root certificates trusted on top of the system ones.
+    pub fn extra_root_certificates(&self) -> &[Certificate] {
+        &self.root_certificates
+    }
+
+    /// Send the requests to the FIPS 140-2 validated endpoint of the region,
+    /// `s3-fips.{region}.amazonaws.com`, when `fips` is `true`.
+    ///
+    /// Only applies to AWS regions, the endpoints of `Region::Custom` and
+    /// of other providers are left as they are.
+    ///
+    /// ```no_run
+    /// use s3::bucket::Bucket;
+    /// use s3::creds::Credentials;
+    ///
+    /// let region = "us-gov-west-1".parse().unwrap();
+    /// let credentials = Credentials::default().unwrap();
+    /// let bucket = Bucket::new("rust-s3-test", region, credentials)
+    ///     .unwrap()
+    ///     .with_fips(true);
+    /// ```
+    pub fn with_fips(mut self, fips: bool) -> Bucket {
+        self.fips = fips;
+        self
+    }
+
+    /// Whether the requests are sent to the FIPS endpoint, see `with_fips`.
+    pub fn fips(&self) -> bool {
+        self.fips
+    }
+
+    /// Send the requests to the dual-stack endpoint of the region, reachable
+    /// over both IPv4 and IPv6, `s3.dualstack.{region}.amazonaws.com`, when
+    /// `dualstack` is `true`.
+    ///
+    /// Only applies to AWS regions, like `with_fips` which it can be combined
+    /// with.
+    pub fn with_dualstack(mut self, dualstack: bool) -> Bucket {
+        self.dualstack = dualstack;
+        self
+    }
+
+    /// Whether the requests are sent to the dual-stack endpoint, see
+    /// `with_dualstack`.
+    pub fn dualstack(&self) -> bool {
+        self.dualstack
     }
 
     /// Get a reference to the name of the S3 bucket.
@@ -1465,11 +3690,48 @@ impl Bucket {
 
     /// Get a paths-style reference to the hostname of the S3 API endpoint.
     pub fn path_style_host(&self) -> String {
-        self.region.host()
+        self.endpoint_host()
     }
 
     pub fn subdomain_style_host(&self) -> String {
-        format!("{}.{}", self.name, self.region.host())
+        format!("{}.{}", self.name, self.endpoint_host())
+    }
+
+    /// Host of the region's endpoint as HTTP clients send it, so that the
+    /// signed `Host` header matches the requested one: without a trailing
+    /// slash, or the default port of the scheme.
+    fn endpoint_host(&self) -> String {
+        if let Some(host) = self.aws_variant_host() {
+            return host;
+        }
+        let host = self.region.host();
+        let host = host.trim_end_matches('/');
+        let default_port = match self.scheme().as_str() {
+            "http" => ":80",
+            _ => ":443",
+        };
+        host.strip_suffix(default_port).unwrap_or(host).to_string()
+    }
+
+    /// Host of the FIPS and/or dual-stack endpoint of AWS regions, which are
+    /// all named after the region, unlike the default endpoints.
+    fn aws_variant_host(&self) -> Option<String> {
+        if !(self.fips || self.dualstack) {
+            return None;
+        }
+        let suffix = self.region.partition().dns_suffix();
+        match self.region {
+            Region::Custom { .. } => return None,
+            _ if !self.region.endpoint().ends_with(suffix) => return None,
+            _ => {}
+        }
+        Some(format!(
+            "s3{}{}.{}.{}",
+            if self.fips { "-fips" } else { "" },
+            if self.dualstack { ".dualstack" } else { "" },
+            self.region,
+            suffix
+        ))
     }
 
     // pub fn self_host(&self) -> String {
@@ -1487,20 +3749,18 @@ impl Bucket {
 
     /// Get a reference to the AWS access key.
     pub fn access_key(&self) -> Option<String> {
-        if let Some(access_key) = self.credentials.access_key.clone() {
-            Some(access_key.replace('\n', ""))
-        } else {
-            None
-        }
+        self.credentials
+            .access_key
+            .as_ref()
+            .map(|access_key| access_key.replace('\n', ""))
     }
 
     /// Get a reference to the AWS secret key.
     pub fn secret_key(&self) -> Option<String> {
-        if let Some(secret_key) = self.credentials.secret_key.clone() {
-            Some(secret_key.replace('\n', ""))
-        } else {
-            None
-        }
+        self.credentials
+            .secret_key
+            .as_ref()
+            .map(|secret_key| secret_key.replace('\n', ""))
     }
 
     /// Get a reference to the AWS security token.
@@ -1513,6 +3773,13 @@ impl Bucket {
         self.credentials.session_token.as_deref()
     }
 
+    /// Whether the bucket uses anonymous credentials, as made by
+    /// `Bucket::new_public`. Its requests are then sent unsigned, which
+    /// public buckets accept for reading.
+    pub fn is_anonymous(&self) -> bool {
+        self.credentials.is_anonymous()
+    }
+
     /// Get a reference to the full [`Credentials`](struct.Credentials.html)
     /// object used by this `Bucket`.
     pub fn credentials(&self) -> &Credentials {
@@ -1575,6 +3842,7 @@ mod test {
 
     use crate::creds::Credentials;
     use crate::region::Region;
+    use crate::serde_types::ContentRange;
     use crate::Bucket;
     use crate::BucketConfiguration;
     use crate::Tag;
@@ -1680,10 +3948,579 @@ mod test {
         .unwrap()
     }
 
+    /// Path style bucket of a local server answering a single request with
+    /// `response`, then closing the connection.
+    fn test_local_bucket(response: &'static str) -> Bucket {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = stream.read(&mut buffer).unwrap();
+                request.extend_from_slice(&buffer[..read]);
+            }
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        Bucket::new_with_path_style(
+            "rust-s3",
+            Region::Custom {
+                region: "eu-central-1".to_owned(),
+                endpoint,
+            },
+            test_minio_credentials(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_endpoint_host() {
+        let bucket = |endpoint: &str| {
+            Bucket::new(
+                "rust-s3",
+                Region::Custom {
+                    region: "auto".to_owned(),
+                    endpoint: endpoint.to_owned(),
+                },
+                test_minio_credentials(),
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            bucket("https://storage.googleapis.com").host(),
+            "rust-s3.storage.googleapis.com"
+        );
+        assert_eq!(
+            bucket("https://storage.googleapis.com:443/").host(),
+            "rust-s3.storage.googleapis.com"
+        );
+        assert_eq!(
+            bucket("https://storage.googleapis.com/")
+                .with_path_style()
+                .url(),
+            "https://storage.googleapis.com/rust-s3"
+        );
+        assert_eq!(
+            bucket("http://localhost:9000").with_path_style().host(),
+            "localhost:9000"
+        );
+        assert_eq!(bucket("http://localhost:80").host(), "rust-s3.localhost");
+        assert_eq!(
+            bucket("http://localhost:443").host(),
+            "rust-s3.localhost:443"
+        );
+    }
+
+    #[test]
+    fn test_path_style_request() {
+        use super::RequestImpl;
+        use crate::command::Command;
+        use crate::request_trait::Request;
+
+        let bucket = Bucket::new(
+            "rust-s3",
+            Region::Custom {
+                region: "eu-central-1".to_owned(),
+                endpoint: "http://localhost:9000".to_owned(),
+            },
+            test_minio_credentials(),
+        )
+        .unwrap();
+        let request = RequestImpl::new(&bucket, "/a.txt", Command::GetObject);
+        assert_eq!(
+            request.url().as_str(),
+            "http://rust-s3.localhost:9000/a.txt"
+        );
+        assert_eq!(request.headers().unwrap()["Host"], "rust-s3.localhost:9000");
+
+        let bucket = bucket.with_path_style();
+        let request = RequestImpl::new(&bucket, "/a.txt", Command::GetObject);
+        assert_eq!(
+            request.url().as_str(),
+            "http://localhost:9000/rust-s3/a.txt"
+        );
+        assert_eq!(request.headers().unwrap()["Host"], "localhost:9000");
+    }
+
+    #[test]
+    fn test_retries_are_opt_in() {
+        use crate::retry::RetryPolicy;
+
+        let bucket = test_minio_bucket();
+        assert_eq!(bucket.retry_policy(), &RetryPolicy::never());
+        assert_eq!(
+            Bucket::new_public("rust-s3", Region::UsEast1)
+                .unwrap()
+                .retry_policy(),
+            &RetryPolicy::never()
+        );
+        let bucket = bucket.with_retries(RetryPolicy::default());
+        assert_eq!(bucket.retry_policy().max_attempts, 3);
+    }
+
+    #[maybe_async::test(
+        feature = "sync",
+        async(all(not(feature = "sync"), feature = "with-tokio"), tokio::test),
+        async(
+            all(not(feature = "sync"), feature = "with-async-std"),
+            async_std::test
+        )
+    )]
+    async fn test_get_object_to_writer() {
+        let complete = "HTTP/1.1 200 OK\r\nContent-Length: 11\r\n\r\nhello world";
+        let truncated = "HTTP/1.1 200 OK\r\nContent-Length: 11\r\n\r\nhello";
+        for response in [complete, truncated] {
+            let bucket = test_local_bucket(response);
+            let mut writer = Vec::new();
+            cfg_if! {
+                if #[cfg(feature = "sync")] {
+                    let result = bucket.get_object_to_writer("/a.txt", &mut writer);
+                } else {
+                    let result = bucket.get_object_stream_to("/a.txt", &mut writer).await;
+                }
+            }
+            if response == complete {
+                assert_eq!(result.unwrap(), (11, 200));
+                assert_eq!(writer, b"hello world");
+            } else {
+                // The bytes received before the connection closed were written
+                assert!(result.is_err());
+                assert_eq!(writer, b"hello");
+            }
+        }
+    }
+
+    #[cfg(all(not(feature = "sync"), feature = "with-tokio"))]
+    #[tokio::test]
+    async fn test_shared_http_client() {
+        use super::RequestImpl;
+        use crate::command::Command;
+        use crate::request_trait::Request;
+
+        let client = reqwest::Client::new();
+        let bucket = test_minio_bucket().with_http_client(client.clone());
+        assert!(bucket.http_client().is_some());
+        // Clients aren't compared, only whether the buckets were given one
+        assert_eq!(bucket, test_minio_bucket().with_http_client(client));
+        assert_ne!(bucket, test_minio_bucket());
+
+        // The TLS settings of the bucket would silently not apply
+        let bucket = bucket.with_danger_accept_invalid_certs(true);
+        let request = RequestImpl::new(&bucket, "/a.txt", Command::GetObject);
+        assert!(request
+            .response()
+            .await
+            .unwrap_err()
+            .to_string()
+            .starts_with("TLS settings of the bucket don't apply"));
+    }
+
+    #[test]
+    fn test_fips_dualstack_host() {
+        use super::RequestImpl;
+        use crate::command::Command;
+        use crate::request_trait::Request;
+
+        let bucket =
+            |region: Region| Bucket::new("rust-s3", region, test_minio_credentials()).unwrap();
+        assert_eq!(
+            bucket(Region::UsEast1).with_fips(true).host(),
+            "rust-s3.s3-fips.us-east-1.amazonaws.com"
+        );
+        assert_eq!(
+            bucket(Region::CnNorth1).with_dualstack(true).host(),
+            "rust-s3.s3.dualstack.cn-north-1.amazonaws.com.cn"
+        );
+        assert_eq!(
+            bucket(Region::UsGovWest1)
+                .with_fips(true)
+                .with_dualstack(true)
+                .with_path_style()
+                .url(),
+            "https://s3-fips.dualstack.us-gov-west-1.amazonaws.com/rust-s3"
+        );
+        assert_eq!(
+            bucket(Region::EuWest1).with_fips(false).host(),
+            "rust-s3.s3-eu-west-1.amazonaws.com"
+        );
+        assert_eq!(
+            bucket(Region::DoFra1).with_fips(true).host(),
+            "rust-s3.fra1.digitaloceanspaces.com"
+        );
+        assert_eq!(
+            bucket(Region::Custom {
+                region: "us-east-1".to_owned(),
+                endpoint: "http://localhost:9000".to_owned(),
+            })
+            .with_dualstack(true)
+            .host(),
+            "rust-s3.localhost:9000"
+        );
+
+        let bucket = bucket(Region::EuWest1).with_dualstack(true);
+        let request = RequestImpl::new(&bucket, "/a.txt", Command::GetObject);
+        let headers = request.headers().unwrap();
+        assert_eq!(
+            headers["Host"],
+            "rust-s3.s3.dualstack.eu-west-1.amazonaws.com"
+        );
+        assert!(headers["Authorization"]
+            .to_str()
+            .unwrap()
+            .contains("/eu-west-1/s3/aws4_request,"));
+    }
+
     fn test_digital_ocean_bucket() -> Bucket {
         Bucket::new("rust-s3", Region::DoFra1, test_digital_ocean_credentials()).unwrap()
     }
 
+    #[test]
+    fn test_stream_part_size() {
+        use super::{stream_part_size, CHUNK_SIZE};
+        assert_eq!(stream_part_size(1), CHUNK_SIZE);
+        assert_eq!(stream_part_size(1000), CHUNK_SIZE);
+        assert_eq!(stream_part_size(1001), 2 * CHUNK_SIZE);
+        assert_eq!(stream_part_size(10000), 2 << 30);
+        let total: usize = (1..=10000).map(stream_part_size).sum();
+        assert!(total >= 5 << 40);
+    }
+
+    #[test]
+    fn test_delete_objects_xml() {
+        use super::parse_delete_result;
+        use crate::command::Command;
+        use crate::serde_types::{DeleteObjectsData, DeleteResult};
+
+        let data = DeleteObjectsData {
+            keys: vec!["a.txt".to_string(), "b & <c>.txt".to_string()],
+        };
+        assert_eq!(
+            data.to_string(),
+            "<Delete><Object><Key>a.txt</Key></Object>\
+             <Object><Key>b &amp; &lt;c&gt;.txt</Key></Object></Delete>"
+        );
+        assert_eq!(
+            Command::DeleteObjects { data: data.clone() }.content_length(),
+            data.to_string().len()
+        );
+
+        let mut result = DeleteResult::default();
+        parse_delete_result(
+            "<DeleteResult xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\">\
+             <Deleted><Key>a.txt</Key></Deleted>\
+             <Error><Key>b.txt</Key><Code>AccessDenied</Code><Message>Access Denied</Message></Error>\
+             <Deleted><Key>c.txt</Key><VersionId>1</VersionId></Deleted>\
+             </DeleteResult>",
+            &mut result,
+        )
+        .unwrap();
+        assert_eq!(result.deleted.len(), 2);
+        assert_eq!(result.deleted[0].key, "a.txt");
+        assert_eq!(result.deleted[1].version_id.as_deref(), Some("1"));
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].key, "b.txt");
+        assert_eq!(result.errors[0].code, "AccessDenied");
+        assert_eq!(result.failed_keys(), ["b.txt"]);
+    }
+
+    #[test]
+    fn test_list_buckets_request() {
+        use super::RequestImpl;
+        use crate::command::Command;
+        use crate::request_trait::Request;
+        use crate::serde_types::ListAllMyBucketsResult;
+        use serde_xml_rs as serde_xml;
+
+        let service = Bucket::new_with_path_style(
+            "",
+            Region::Custom {
+                region: "eu-central-1".to_owned(),
+                endpoint: "http://localhost:9000".to_owned(),
+            },
+            test_minio_credentials(),
+        )
+        .unwrap();
+        let request = RequestImpl::new(&service, "", Command::ListBuckets);
+        assert_eq!(request.url().as_str(), "http://localhost:9000/");
+        assert_eq!(request.headers().unwrap()["Host"], "localhost:9000");
+
+        let result: ListAllMyBucketsResult = serde_xml::from_str(
+            "<ListAllMyBucketsResult xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\">\
+             <Owner><ID>bcaf1ffd86f461ca5fb16fd081034f</ID><DisplayName>webfile</DisplayName></Owner>\
+             <Buckets>\
+             <Bucket><Name>quotes</Name><CreationDate>2006-02-03T16:45:09.000Z</CreationDate></Bucket>\
+             <Bucket><Name>samples</Name><CreationDate>2006-02-03T16:41:58.000Z</CreationDate></Bucket>\
+             </Buckets></ListAllMyBucketsResult>",
+        )
+        .unwrap();
+        assert_eq!(result.owner.unwrap().display_name, "webfile");
+        let names: Vec<_> = result.buckets.bucket.iter().map(|b| &b.name).collect();
+        assert_eq!(names, ["quotes", "samples"]);
+        assert_eq!(
+            result.buckets.bucket[1].creation_date,
+            "2006-02-03T16:41:58.000Z"
+        );
+
+        let result: ListAllMyBucketsResult = serde_xml::from_str(
+            "<ListAllMyBucketsResult><Buckets></Buckets></ListAllMyBucketsResult>",
+        )
+        .unwrap();
+        assert!(result.buckets.bucket.is_empty());
+    }
+
+    #[test]
+    fn test_tagging() {
+        use super::{parse_tagging, validate_tags};
+
+        let bucket = test_minio_bucket();
+        assert_eq!(
+            bucket._tags_xml(&[("Team", "a&b")]),
+            "<Tagging><TagSet><Tag><Key>Team</Key><Value>a&amp;b</Value></Tag></TagSet></Tagging>"
+        );
+
+        let tags = parse_tagging(
+            "<Tagging xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\"><TagSet>\
+             <Tag><Key>Team</Key><Value>a&amp;b</Value></Tag>\
+             <Tag><Key>Tier</Key><Value></Value></Tag>\
+             </TagSet></Tagging>",
+        );
+        assert_eq!(
+            tags,
+            vec![
+                Tag {
+                    key: "Team".to_string(),
+                    value: "a&b".to_string()
+                },
+                Tag {
+                    key: "Tier".to_string(),
+                    value: "".to_string()
+                }
+            ]
+        );
+
+        assert!(validate_tags(&[("Team", "a")]).is_ok());
+        let too_many: Vec<(String, String)> =
+            (0..11).map(|i| (i.to_string(), String::new())).collect();
+        assert!(validate_tags(&too_many).is_err());
+        assert!(validate_tags(&too_many[..10]).is_ok());
+        assert!(validate_tags(&[("k".repeat(129), String::new())]).is_err());
+        assert!(validate_tags(&[("k".repeat(128), "v".repeat(256))]).is_ok());
+        assert!(validate_tags(&[("k".to_string(), "v".repeat(257))]).is_err());
+        assert_eq!(
+            validate_tags(&[("Team", "a"), ("Tier", "b"), ("Team", "c")])
+                .unwrap_err()
+                .to_string(),
+            "Tag keys must be unique, got \"Team\" twice"
+        );
+        assert!(validate_tags(&[("", "v")]).is_err());
+    }
+
+    #[test]
+    fn test_check_status_s3_error() {
+        use super::check_status;
+        use crate::serde_types::S3Error;
+
+        assert!(check_status("GetObject", 200, b"").is_ok());
+
+        let error = check_status(
+            "GetObject",
+            404,
+            b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+              <Error><Code>NoSuchKey</Code><Message>The resource you requested does not exist</Message>\
+              <Resource>/mybucket/myfoto.jpg</Resource><RequestId>4442587FB7D0A2F9</RequestId></Error>",
+        )
+        .unwrap_err();
+        let s3_error = error.downcast_ref::<S3Error>().unwrap();
+        assert_eq!(
+            *s3_error,
+            S3Error {
+                code: "NoSuchKey".to_string(),
+                message: "The resource you requested does not exist".to_string(),
+                request_id: Some("4442587FB7D0A2F9".to_string()),
+                resource: Some("/mybucket/myfoto.jpg".to_string()),
+                status: 404,
+            }
+        );
+
+        let error = check_status("HeadObject", 403, b"").unwrap_err();
+        let s3_error = error.downcast_ref::<S3Error>().unwrap();
+        assert_eq!(s3_error.code, "");
+        assert_eq!(s3_error.status, 403);
+
+        assert_eq!(
+            S3Error::check_response((b"data".to_vec(), 200)).unwrap(),
+            b"data"
+        );
+        assert!(S3Error::check_response((Vec::new(), 304)).is_ok());
+        let error = S3Error::check_response((
+            b"<Error><Code>AccessDenied</Code><Message>Access Denied</Message></Error>".to_vec(),
+            403,
+        ))
+        .unwrap_err();
+        assert_eq!(error.code, "AccessDenied");
+        assert_eq!(error.status, 403);
+    }
+
+    #[test]
+    fn test_copy_source() {
+        use super::copy_source;
+        assert_eq!(
+            copy_source("bucket", "/dir/my file ü.txt"),
+            "/bucket/dir/my%20file%20%C3%BC.txt"
+        );
+        assert_eq!(copy_source("bucket", "a+b"), "/bucket/a%2Bb");
+    }
+
+    #[test]
+    fn test_check_stream_size() {
+        use super::check_stream_size;
+        assert!(check_stream_size(None, 3).is_ok());
+        assert!(check_stream_size(Some(3), 3).is_ok());
+        assert_eq!(
+            check_stream_size(Some(4), 3).unwrap_err().to_string(),
+            "Expected a stream of 4 bytes, read 3"
+        );
+    }
+
+    #[test]
+    fn test_validate_parts() {
+        use super::{validate_part_number, validate_parts, MIN_PART_SIZE};
+        use crate::serde_types::Part;
+
+        let part = |part_number, size| Part {
+            part_number,
+            size,
+            ..Part::default()
+        };
+        assert!(validate_part_number(0).is_err());
+        assert!(validate_part_number(1).is_ok());
+        assert!(validate_part_number(10000).is_ok());
+        assert!(validate_part_number(10001).is_err());
+
+        assert!(validate_parts(&[part(1, Some(MIN_PART_SIZE)), part(3, Some(1))]).is_ok());
+        assert!(validate_parts(&[part(1, None), part(2, None)]).is_ok());
+        assert!(validate_parts(&[part(1, Some(1))]).is_ok());
+        assert!(validate_parts(&[]).is_err());
+        assert_eq!(
+            validate_parts(&[part(2, None), part(1, None)])
+                .unwrap_err()
+                .to_string(),
+            "Parts must be in ascending order of part number, got 1 after 2"
+        );
+        assert_eq!(
+            validate_parts(&[part(1, Some(1)), part(2, Some(1))])
+                .unwrap_err()
+                .to_string(),
+            "Part 1 is 1 bytes, only the last part may be smaller than 5242880"
+        );
+    }
+
+    #[test]
+    fn test_copy_metadata() {
+        use super::{copy_metadata, RequestImpl, MAX_COPY_SIZE};
+        use crate::command::Command;
+        use crate::request_trait::Request;
+        use crate::serde_types::HeadObjectResult;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("content-length", (MAX_COPY_SIZE + 1).into());
+        headers.insert("content-type", "image/png".parse().unwrap());
+        headers.insert("cache-control", "max-age=60".parse().unwrap());
+        headers.insert("x-amz-meta-author", "me".parse().unwrap());
+        headers.insert(
+            "etag",
+            "\"5d41402abc4b2a76b9719d911017c592-3\"".parse().unwrap(),
+        );
+        let head = HeadObjectResult::from(&headers);
+        assert!(head.content_length.unwrap() as u64 > MAX_COPY_SIZE);
+
+        let metadata = copy_metadata(&head).unwrap();
+        assert_eq!(metadata.len(), 3);
+        let bucket = test_minio_bucket();
+        let command = Command::InitiateMultipartUpload {
+            metadata: Some(metadata),
+        };
+        let request = RequestImpl::new(&bucket, "/copy", command);
+        let headers = request.headers().unwrap();
+        assert_eq!(headers["content-type"], "image/png");
+        assert_eq!(headers["cache-control"], "max-age=60");
+        assert_eq!(headers["x-amz-meta-author"], "me");
+        assert!(!headers.contains_key("etag"));
+    }
+
+    #[test]
+    fn test_uploaded_part() {
+        use super::uploaded_part;
+        use crate::checksum::ChecksumAlgorithm;
+        use crate::serde_types::{ListPartsResult, Part};
+        use serde_xml_rs as serde_xml;
+
+        let page: ListPartsResult = serde_xml::from_str(
+            "<ListPartsResult><Bucket>b</Bucket><Key>k</Key><UploadId>id</UploadId>\
+             <NextPartNumberMarker>2</NextPartNumberMarker><IsTruncated>true</IsTruncated>\
+             <Part><PartNumber>1</PartNumber><ETag>\"5d41402abc4b2a76b9719d911017c592\"</ETag>\
+             <Size>5</Size></Part>\
+             <Part><PartNumber>2</PartNumber><ETag>\"7d793037a0760186574b0282f2f435e7\"</ETag>\
+             <Size>5</Size></Part></ListPartsResult>",
+        )
+        .unwrap();
+        assert!(page.is_truncated);
+        assert_eq!(page.next_part_number_marker, Some(2));
+        assert_eq!(page.parts.len(), 2);
+
+        assert_eq!(
+            uploaded_part(&page.parts, 1, b"hello"),
+            Some(Part {
+                part_number: 1,
+                etag: "\"5d41402abc4b2a76b9719d911017c592\"".to_string(),
+                size: Some(5),
+                ..Part::default()
+            })
+        );
+        assert_eq!(uploaded_part(&page.parts, 2, b"hello"), None);
+        assert_eq!(uploaded_part(&page.parts, 3, b"world"), None);
+        assert!(uploaded_part(&page.parts, 2, b"world").is_some());
+
+        // Encrypted parts, whose ETags aren't MD5s
+        let encrypted = [Part {
+            part_number: 1,
+            etag: "\"a3f1c0ffee\"".to_string(),
+            size: Some(5),
+            ..Part::default()
+        }];
+        assert!(uploaded_part(&encrypted, 1, b"hello").is_some());
+        assert_eq!(uploaded_part(&encrypted, 1, b"hello!"), None);
+        let checksummed = [Part {
+            checksum_sha256: Some(ChecksumAlgorithm::Sha256.checksum(b"hello")),
+            ..encrypted[0].clone()
+        }];
+        assert!(uploaded_part(&checksummed, 1, b"hello").is_some());
+        assert_eq!(uploaded_part(&checksummed, 1, b"world"), None);
+    }
+
+    #[test]
+    fn test_next_page_token() {
+        use super::next_page_token;
+        use crate::serde_types::ListBucketResult;
+        use serde_xml_rs as serde_xml;
+        let page = |truncated: bool, token: &str| -> ListBucketResult {
+            serde_xml::from_str(&format!(
+                "<ListBucketResult><Name>b</Name><Prefix></Prefix><MaxKeys>1</MaxKeys>\
+                 <IsTruncated>{}</IsTruncated>{}</ListBucketResult>",
+                truncated, token
+            ))
+            .unwrap()
+        };
+        let token = "<NextContinuationToken>abc</NextContinuationToken>";
+        assert_eq!(
+            next_page_token(&page(true, token)),
+            Some(Some("abc".to_string()))
+        );
+        assert_eq!(next_page_token(&page(false, token)), None);
+        assert_eq!(next_page_token(&page(true, "")), None);
+    }
+
     fn object(size: u32) -> Vec<u8> {
         (0..size).map(|_| 33).collect()
     }
@@ -1709,6 +4546,27 @@ mod test {
         // println!("{}", std::str::from_utf8(&data).unwrap());
         assert_eq!(test[100..1001].to_vec(), data);
 
+        let (object, code) = bucket
+            .get_object_range_with_headers(s3_path, 3000, None)
+            .await
+            .unwrap();
+        assert_eq!(code, 206);
+        assert_eq!(test[3000..].to_vec(), object.data);
+        assert_eq!(
+            object.content_range,
+            Some(ContentRange {
+                start: 3000,
+                end: 3071,
+                total_size: Some(3072)
+            })
+        );
+
+        let (object, code) = bucket.get_object_with_headers(s3_path).await.unwrap();
+        assert_eq!(code, 200);
+        assert_eq!(test, object.data);
+        assert_eq!(object.content_length, Some(3072));
+        assert!(object.etag.is_some());
+
         let (head_object_result, code) = bucket.head_object(s3_path).await.unwrap();
         assert_eq!(code, 200);
         assert_eq!(
@@ -1716,8 +4574,12 @@ mod test {
             "application/octet-stream".to_owned()
         );
         // println!("{:?}", head_object_result);
+        let exists = bucket.object_exists(s3_path).await.unwrap();
+        assert!(exists);
         let (_, code) = bucket.delete_object(s3_path).await.unwrap();
         assert_eq!(code, 204);
+        let exists = bucket.object_exists(s3_path).await.unwrap();
+        assert!(!exists);
     }
 
     #[ignore]
@@ -1771,7 +4633,7 @@ mod test {
         init();
         let remote_path = "+stream_test_big";
         let local_path = "+stream_test_big";
-        std::fs::remove_file(remote_path).unwrap_or_else(|_| {});
+        std::fs::remove_file(remote_path).unwrap_or(());
         let bucket = test_aws_bucket();
         let test: Vec<u8> = object(10_000_000);
 
@@ -1787,17 +4649,19 @@ mod test {
             }
         }
 
-        let code = bucket
+        let (etag, code) = bucket
             .put_object_stream(&mut reader, remote_path)
             .await
             .unwrap();
         assert_eq!(code, 200);
+        assert!(etag.ends_with("-2\""));
         let mut writer = Vec::new();
-        let code = bucket
-            .get_object_stream(local_path, &mut writer)
+        let (written, code) = bucket
+            .get_object_stream_with_size(local_path, &mut writer)
             .await
             .unwrap();
         assert_eq!(code, 200);
+        assert_eq!(written, test.len() as u64);
         assert_eq!(test, writer);
         let (body, code) = bucket.get_object_torrent(remote_path).await.unwrap();
         // let dummy: Vec<u8> = Vec::new();
@@ -1867,7 +4731,7 @@ mod test {
         );
         let (_, code) = bucket.delete_object(local_path).await.unwrap();
         assert_eq!(code, 204);
-        std::fs::remove_file(local_path).unwrap_or_else(|_| {});
+        std::fs::remove_file(local_path).unwrap_or(());
     }
 
     #[cfg(feature = "blocking")]
@@ -2061,7 +4925,7 @@ mod test {
 
         // assert_eq!(url, "");
 
-        assert!(url.contains("host%3Bcustom_header"));
+        assert!(url.contains("custom_header%3Bhost"));
         assert!(url.contains("/test%2Ftest.file"))
     }
 
@@ -2075,6 +4939,186 @@ mod test {
         assert!(url.contains("/test%2Ftest.file?"))
     }
 
+    #[test]
+    fn test_minio_canonical_requests() {
+        use super::RequestImpl;
+        use crate::command::Command;
+        use crate::request_trait::Request;
+
+        // Both the requests and presigned URLs are signed for the port and
+        // plain HTTP scheme of the endpoint
+        let bucket = test_minio_bucket();
+        let request = RequestImpl::new(&bucket, "/a.txt", Command::GetObject);
+        let headers = request.headers().unwrap();
+        let canonical_request = request.canonical_request(&headers).unwrap();
+        assert!(canonical_request.starts_with("GET\n/rust-s3/a.txt\n"));
+        assert!(canonical_request.contains("\nhost:localhost:9000\n"));
+
+        let request = RequestImpl::new(
+            &bucket,
+            "/a.txt",
+            Command::PresignGet {
+                expiry_secs: 60,
+                custom_queries: None,
+                custom_headers: None,
+            },
+        );
+        let url = request.presigned().unwrap();
+        assert!(url.starts_with("http://localhost:9000/rust-s3/a.txt?"));
+        let mut headers = HeaderMap::new();
+        headers.insert(http::header::HOST, request.host_header().parse().unwrap());
+        assert!(request
+            .presigned_canonical_request(&headers)
+            .unwrap()
+            .contains("\nhost:localhost:9000\n"));
+    }
+
+    #[test]
+    fn test_presign_get_with() {
+        use super::RequestImpl;
+        use crate::command::Command;
+        use crate::request_trait::Request;
+        use std::collections::HashMap;
+        use url::Url;
+
+        let mut bucket = test_minio_bucket();
+        bucket.add_query("versionId", "1");
+        let mut custom_queries = HashMap::new();
+        custom_queries.insert(
+            "response-content-disposition".to_string(),
+            "attachment; filename=\"a.txt\"".to_string(),
+        );
+        let mut custom_headers = HeaderMap::new();
+        custom_headers.insert(http::header::RANGE, "bytes=0-9".parse().unwrap());
+
+        let url = bucket
+            .presign_get_with(
+                "/a.txt",
+                60,
+                Some(custom_queries.clone()),
+                Some(custom_headers.clone()),
+            )
+            .unwrap();
+        let url = Url::parse(&url).unwrap();
+        let query: HashMap<_, _> = url.query_pairs().into_owned().collect();
+        assert_eq!(query["versionId"], "1");
+        assert_eq!(
+            query["response-content-disposition"],
+            "attachment; filename=\"a.txt\""
+        );
+        assert_eq!(query["X-Amz-SignedHeaders"], "host;range");
+
+        // The signature covers the queries and headers
+        let request = RequestImpl::new(
+            &bucket,
+            "/a.txt",
+            Command::PresignGet {
+                expiry_secs: 60,
+                custom_queries: Some(custom_queries),
+                custom_headers: Some(custom_headers.clone()),
+            },
+        );
+        let mut headers = custom_headers;
+        headers.insert(http::header::HOST, request.host_header().parse().unwrap());
+        let canonical_request = request.presigned_canonical_request(&headers).unwrap();
+        assert!(canonical_request.contains(
+            "&response-content-disposition=attachment%3B%20filename%3D%22a.txt%22&versionId=1\n"
+        ));
+        assert!(
+            canonical_request.contains("\nhost:localhost:9000\nrange:bytes=0-9\n\nhost;range\n")
+        );
+
+        // The one week limit is checked once, by presign_get_with
+        assert!(bucket.presign_get("/a.txt", 604800).is_ok());
+        assert!(bucket.presign_get("/a.txt", 604801).is_err());
+    }
+
+    #[test]
+    fn test_presign_post() {
+        use crate::post_policy::PostPolicyCondition;
+
+        let bucket = test_minio_bucket();
+        let post = bucket
+            .presign_post(
+                "uploads/",
+                3600,
+                vec![
+                    PostPolicyCondition::Eq {
+                        field: "Content-Type".to_string(),
+                        value: "image/png".to_string(),
+                    },
+                    PostPolicyCondition::ContentLengthRange { min: 1, max: 1024 },
+                ],
+            )
+            .unwrap();
+        assert_eq!(post.url, "http://localhost:9000/rust-s3");
+        assert_eq!(post.fields["key"], "uploads/${filename}");
+        assert_eq!(post.fields["Content-Type"], "image/png");
+        assert_eq!(post.fields["x-amz-algorithm"], "AWS4-HMAC-SHA256");
+        assert!(post.fields["x-amz-credential"].starts_with("test/"));
+        assert!(post.fields["x-amz-credential"].ends_with("/eu-central-1/s3/aws4_request"));
+        assert_eq!(post.fields["x-amz-signature"].len(), 64);
+
+        let policy = String::from_utf8(base64::decode(&post.fields["policy"]).unwrap()).unwrap();
+        assert!(policy.contains("[\"eq\",\"$bucket\",\"rust-s3\"]"));
+        assert!(policy.contains("[\"starts-with\",\"$key\",\"uploads/\"]"));
+        assert!(policy.contains(&format!(
+            "[\"eq\",\"$x-amz-date\",\"{}\"]",
+            post.fields["x-amz-date"]
+        )));
+        assert!(policy.contains("[\"eq\",\"$Content-Type\",\"image/png\"]"));
+        assert!(policy.contains("[\"content-length-range\",1,1024]"));
+
+        assert!(Bucket::new_public("rust-s3", Region::UsEast1)
+            .unwrap()
+            .presign_post("", 3600, Vec::new())
+            .is_err());
+
+        let post = bucket
+            .presign_post(
+                "uploads/",
+                3600,
+                vec![PostPolicyCondition::Eq {
+                    field: "key".to_string(),
+                    value: "uploads/avatar.png".to_string(),
+                }],
+            )
+            .unwrap();
+        assert_eq!(post.fields["key"], "uploads/avatar.png");
+        assert_eq!(
+            bucket
+                .presign_post(
+                    "uploads/",
+                    3600,
+                    vec![PostPolicyCondition::Eq {
+                        field: "key".to_string(),
+                        value: "avatar.png".to_string(),
+                    }],
+                )
+                .unwrap_err()
+                .to_string(),
+            "The key \"avatar.png\" of a presigned POST must start with \"uploads/\""
+        );
+        assert!(bucket
+            .presign_post(
+                "uploads/",
+                3600,
+                vec![PostPolicyCondition::ContentLengthRange { min: 10, max: 1 }],
+            )
+            .is_err());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_presign_delete() {
+        let s3_path = "/test/test.file";
+        let bucket = test_aws_bucket();
+
+        let url = bucket.presign_delete(s3_path, 86400).unwrap();
+        assert!(url.contains("/test%2Ftest.file?"));
+        assert!(bucket.presign_delete(s3_path, 604801).is_err());
+    }
+
     #[maybe_async::test(
         feature = "sync",
         async(all(not(feature = "sync"), feature = "with-tokio"), tokio::test),
@@ -0,0 +1,309 @@
+//! Additional checksums of object contents, sent and checked through the
+//! `x-amz-checksum-*` headers.
+
+use anyhow::{anyhow, Result};
+use http::header::HeaderName;
+use http::HeaderMap;
+use sha2::{Digest, Sha256};
+
+use crate::serde_types::Part;
+
+/// Algorithm of the checksums a `Bucket` sends with uploads and checks on
+/// downloads, see `Bucket::with_checksum`.
+///
+/// Unlike ETags, which are not the MD5 of the content for multipart uploads
+/// or encrypted objects, these checksums are always computed over the
+/// content, part by part for multipart uploads.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChecksumAlgorithm {
+    Crc32c,
+    Sha256,
+}
+
+impl ChecksumAlgorithm {
+    pub(crate) const ALL: [ChecksumAlgorithm; 2] =
+        [ChecksumAlgorithm::Crc32c, ChecksumAlgorithm::Sha256];
+
+    /// Name of the algorithm in `x-amz-checksum-algorithm`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Crc32c => "CRC32C",
+            ChecksumAlgorithm::Sha256 => "SHA256",
+        }
+    }
+
+    /// Header the checksum is sent and returned in.
+    pub fn header(&self) -> HeaderName {
+        match self {
+            ChecksumAlgorithm::Crc32c => HeaderName::from_static("x-amz-checksum-crc32c"),
+            ChecksumAlgorithm::Sha256 => HeaderName::from_static("x-amz-checksum-sha256"),
+        }
+    }
+
+    /// Base64 encoded checksum of `content`.
+    pub fn checksum(&self, content: &[u8]) -> String {
+        base64::encode(self.digest(content))
+    }
+
+    /// Checksum S3 computes for a multipart upload: the checksum of the
+    /// concatenated digests of its parts, followed by their number.
+    pub fn composite_checksum(&self, parts: &[Part]) -> Result<String> {
+        let mut digests = Vec::new();
+        for part in parts {
+            let checksum = self.part_checksum(part).ok_or_else(|| {
+                anyhow!("Part {} has no {} checksum", part.part_number, self.name())
+            })?;
+            digests.extend(base64::decode(checksum)?);
+        }
+        Ok(format!("{}-{}", self.checksum(&digests), parts.len()))
+    }
+
+    /// `part`, with the checksum of its `content`.
+    pub(crate) fn with_part_checksum(&self, part: Part, content: &[u8]) -> Part {
+        let checksum = Some(self.checksum(content));
+        match self {
+            ChecksumAlgorithm::Crc32c => Part {
+                checksum_crc32c: checksum,
+                ..part
+            },
+            ChecksumAlgorithm::Sha256 => Part {
+                checksum_sha256: checksum,
+                ..part
+            },
+        }
+    }
+
+    pub(crate) fn part_checksum<'a>(&self, part: &'a Part) -> Option<&'a str> {
+        match self {
+            ChecksumAlgorithm::Crc32c => part.checksum_crc32c.as_deref(),
+            ChecksumAlgorithm::Sha256 => part.checksum_sha256.as_deref(),
+        }
+    }
+
+    fn digest(&self, content: &[u8]) -> Vec<u8> {
+        let mut hasher = Hasher::new(*self);
+        hasher.update(content);
+        hasher.finish()
+    }
+}
+
+/// Digest of content fed piece by piece.
+#[derive(Clone)]
+enum Hasher {
+    Crc32c(u32),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    fn new(algorithm: ChecksumAlgorithm) -> Hasher {
+        match algorithm {
+            ChecksumAlgorithm::Crc32c => Hasher::Crc32c(!0),
+            ChecksumAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+        }
+    }
+
+    fn update(&mut self, content: &[u8]) {
+        match self {
+            Hasher::Crc32c(crc) => *crc = crc32c_update(*crc, content),
+            Hasher::Sha256(sha) => sha.update(content),
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        match self {
+            Hasher::Crc32c(crc) => (!crc).to_be_bytes().to_vec(),
+            Hasher::Sha256(sha) => sha.finalize().to_vec(),
+        }
+    }
+}
+
+/// Checks content received piece by piece, such as streamed downloads,
+/// against the checksums S3 returned in the response headers, see
+/// `verify_checksum`.
+pub(crate) struct ChecksumVerifier {
+    expected: Vec<(ChecksumAlgorithm, String, Hasher)>,
+}
+
+impl ChecksumVerifier {
+    /// Checksums of multipart uploads are of their parts, and can't be
+    /// checked against the whole content: those in `headers` are skipped.
+    pub(crate) fn new(headers: &HeaderMap) -> ChecksumVerifier {
+        let expected = ChecksumAlgorithm::ALL
+            .iter()
+            .filter_map(|algorithm| {
+                let expected = headers.get(algorithm.header())?.to_str().ok()?;
+                if expected.contains('-') {
+                    return None;
+                }
+                Some((*algorithm, expected.to_string(), Hasher::new(*algorithm)))
+            })
+            .collect();
+        ChecksumVerifier { expected }
+    }
+
+    pub(crate) fn update(&mut self, content: &[u8]) {
+        for (_, _, hasher) in &mut self.expected {
+            hasher.update(content);
+        }
+    }
+
+    /// Fail if the content fed so far doesn't match the checksums.
+    pub(crate) fn verify(self) -> Result<()> {
+        for (algorithm, expected, hasher) in self.expected {
+            let actual = base64::encode(hasher.finish());
+            if actual != expected {
+                return Err(anyhow!(
+                    "{} checksum mismatch, expected {}, got {}",
+                    algorithm.name(),
+                    expected,
+                    actual
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check `content` against the checksum S3 returned in `headers`, whichever
+/// algorithm it was uploaded with. Checksums of multipart uploads are of
+/// their parts, and can't be checked against the whole content.
+pub(crate) fn verify_checksum(content: &[u8], headers: &HeaderMap) -> Result<()> {
+    let mut verifier = ChecksumVerifier::new(headers);
+    verifier.update(content);
+    verifier.verify()
+}
+
+const CRC32C_TABLE: [u32; 256] = crc32c_table();
+
+const fn crc32c_table() -> [u32; 256] {
+    // Reversed Castagnoli polynomial
+    const POLYNOMIAL: u32 = 0x82f6_3b78;
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+#[cfg(test)]
+fn crc32c(content: &[u8]) -> u32 {
+    !crc32c_update(!0, content)
+}
+
+/// `crc`, before its final inversion, updated with `content`.
+fn crc32c_update(crc: u32, content: &[u8]) -> u32 {
+    content.iter().fold(crc, |crc, byte| {
+        CRC32C_TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serde_types::CompleteMultipartUploadData;
+
+    #[test]
+    fn test_checksum() {
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+        assert_eq!(crc32c(b""), 0);
+        assert_eq!(ChecksumAlgorithm::Crc32c.checksum(b"123456789"), "4waSgw==");
+        assert_eq!(
+            ChecksumAlgorithm::Sha256.checksum(b""),
+            "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="
+        );
+    }
+
+    #[test]
+    fn test_composite_checksum() {
+        let algorithm = ChecksumAlgorithm::Crc32c;
+        let parts: Vec<Part> = [&b"first"[..], &b"second"[..]]
+            .iter()
+            .enumerate()
+            .map(|(i, content)| {
+                let part = Part {
+                    part_number: i as u32 + 1,
+                    etag: String::new(),
+                    ..Part::default()
+                };
+                algorithm.with_part_checksum(part, content)
+            })
+            .collect();
+        let mut digests = crc32c(b"first").to_be_bytes().to_vec();
+        digests.extend(&crc32c(b"second").to_be_bytes());
+        assert_eq!(
+            algorithm.composite_checksum(&parts).unwrap(),
+            format!("{}-2", algorithm.checksum(&digests))
+        );
+        assert!(ChecksumAlgorithm::Sha256
+            .composite_checksum(&parts)
+            .is_err());
+
+        let data = CompleteMultipartUploadData {
+            parts: parts[..1].to_vec(),
+        };
+        assert_eq!(
+            data.to_string(),
+            format!(
+                "<CompleteMultipartUpload><Part><PartNumber>1</PartNumber><ETag></ETag>\
+                 <ChecksumCRC32C>{}</ChecksumCRC32C></Part></CompleteMultipartUpload>",
+                algorithm.checksum(b"first")
+            )
+        );
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let mut headers = HeaderMap::new();
+        assert!(verify_checksum(b"content", &headers).is_ok());
+        headers.insert(
+            ChecksumAlgorithm::Crc32c.header(),
+            ChecksumAlgorithm::Crc32c
+                .checksum(b"content")
+                .parse()
+                .unwrap(),
+        );
+        assert!(verify_checksum(b"content", &headers).is_ok());
+        assert!(verify_checksum(b"corrupted", &headers).is_err());
+        headers.insert(
+            ChecksumAlgorithm::Crc32c.header(),
+            "AAAAAA==-2".parse().unwrap(),
+        );
+        assert!(verify_checksum(b"corrupted", &headers).is_ok());
+    }
+
+    #[test]
+    fn test_checksum_verifier() {
+        let mut headers = HeaderMap::new();
+        for algorithm in ChecksumAlgorithm::ALL.iter() {
+            headers.insert(
+                algorithm.header(),
+                algorithm.checksum(b"streamed content").parse().unwrap(),
+            );
+        }
+        let mut verifier = ChecksumVerifier::new(&headers);
+        assert_eq!(verifier.expected.len(), 2);
+        verifier.update(b"streamed ");
+        verifier.update(b"content");
+        assert!(verifier.verify().is_ok());
+
+        let mut verifier = ChecksumVerifier::new(&headers);
+        verifier.update(b"streamed");
+        assert!(verifier
+            .verify()
+            .unwrap_err()
+            .to_string()
+            .starts_with("CRC32C checksum mismatch"));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::serde_types::CompleteMultipartUploadData;
+use crate::serde_types::{CompleteMultipartUploadData, DeleteObjectsData};
 
 use crate::EMPTY_PAYLOAD_SHA;
 use sha2::{Digest, Sha256};
@@ -11,6 +11,7 @@ pub enum HttpMethod {
     Head,
 }
 
+use std::collections::HashMap;
 use std::fmt;
 
 impl fmt::Display for HttpMethod {
@@ -24,7 +25,18 @@ impl fmt::Display for HttpMethod {
         }
     }
 }
+
+impl HttpMethod {
+    /// Whether sending the request twice has the same effect as sending it
+    /// once, so that it can be retried.
+    pub fn is_idempotent(&self) -> bool {
+        !matches!(self, HttpMethod::Post)
+    }
+}
 use crate::bucket_ops::BucketConfiguration;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use http::header::{HeaderName, IF_MATCH, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_UNMODIFIED_SINCE};
 use http::HeaderMap;
 
 #[derive(Clone, Debug)]
@@ -49,12 +61,95 @@ impl<'a> Multipart<'a> {
     }
 }
 
+/// Preconditions of a conditional request, S3 answers `304 Not Modified` to a
+/// GET when `if_none_match` or `if_modified_since` fail, and `412
+/// Precondition Failed` otherwise.
+///
+/// ```
+/// use s3::command::Conditions;
+///
+/// let conditions = Conditions {
+///     if_none_match: Some("\"9b2cf535f27731c974343645a3985328\"".to_string()),
+///     ..Conditions::default()
+/// };
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Conditions {
+    /// Only proceed if the object's ETag is this one.
+    pub if_match: Option<String>,
+    /// Only proceed if the object's ETag isn't this one, `*` to only create
+    /// objects that don't exist yet.
+    pub if_none_match: Option<String>,
+    /// Only proceed if the object was modified since.
+    pub if_modified_since: Option<DateTime<Utc>>,
+    /// Only proceed if the object wasn't modified since.
+    pub if_unmodified_since: Option<DateTime<Utc>>,
+}
+
+impl Conditions {
+    /// The `If-*` headers of these conditions.
+    pub fn headers(&self) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        if let Some(etag) = &self.if_match {
+            headers.insert(IF_MATCH, etag.parse()?);
+        }
+        if let Some(etag) = &self.if_none_match {
+            headers.insert(IF_NONE_MATCH, etag.parse()?);
+        }
+        if let Some(date) = &self.if_modified_since {
+            headers.insert(IF_MODIFIED_SINCE, http_date(date).parse()?);
+        }
+        if let Some(date) = &self.if_unmodified_since {
+            headers.insert(IF_UNMODIFIED_SINCE, http_date(date).parse()?);
+        }
+        Ok(headers)
+    }
+
+    /// The `x-amz-copy-source-if-*` headers of these conditions on the source
+    /// of a copy, which S3 answers with `412 Precondition Failed` when they
+    /// don't hold.
+    pub fn copy_source_headers(&self) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in self.headers()?.iter() {
+            let name = format!("x-amz-copy-source-{}", name);
+            headers.insert(HeaderName::from_bytes(name.as_bytes())?, value.clone());
+        }
+        Ok(headers)
+    }
+}
+
+fn http_date(date: &DateTime<Utc>) -> String {
+    date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+fn has_conditions(headers: &HeaderMap) -> bool {
+    [
+        IF_MATCH,
+        IF_NONE_MATCH,
+        IF_MODIFIED_SINCE,
+        IF_UNMODIFIED_SINCE,
+    ]
+    .iter()
+    .any(|name| headers.contains_key(name))
+}
+
 #[derive(Clone, Debug)]
 pub enum Command<'a> {
     HeadObject,
     DeleteObject,
     DeleteObjectTagging,
     GetObject,
+    GetObjectIf {
+        conditions: Conditions,
+    },
+    /// Get an object encrypted with SSE-C, sending its key in `sse_headers`,
+    /// see `ServerSideEncryption::read_headers`.
+    GetObjectSse {
+        sse_headers: HeaderMap,
+    },
+    HeadObjectSse {
+        sse_headers: HeaderMap,
+    },
     GetObjectTorrent,
     GetObjectRange {
         start: u64,
@@ -65,6 +160,7 @@ pub enum Command<'a> {
         content: &'a [u8],
         content_type: &'a str,
         multipart: Option<Multipart<'a>>,
+        custom_headers: Option<HeaderMap>,
     },
     PutObjectTagging {
         tags: &'a str,
@@ -83,14 +179,26 @@ pub enum Command<'a> {
         max_keys: Option<usize>,
     },
     GetBucketLocation,
+    /// `custom_queries` are added to the URL, such as `response-*` ones
+    /// overriding headers of the response, and `custom_headers` are signed,
+    /// the client then has to send them.
     PresignGet {
         expiry_secs: u32,
+        custom_queries: Option<HashMap<String, String>>,
+        custom_headers: Option<HeaderMap>,
     },
     PresignPut {
         expiry_secs: u32,
         custom_headers: Option<HeaderMap>,
     },
-    InitiateMultipartUpload,
+    PresignDelete {
+        expiry_secs: u32,
+    },
+    /// `metadata` holds headers of the object, such as `content-type` or
+    /// `x-amz-meta-*` ones.
+    InitiateMultipartUpload {
+        metadata: Option<HeaderMap>,
+    },
     UploadPart {
         part_number: u32,
         content: &'a [u8],
@@ -99,6 +207,26 @@ pub enum Command<'a> {
     AbortMultipartUpload {
         upload_id: &'a str,
     },
+    ListParts {
+        upload_id: &'a str,
+        part_number_marker: Option<u32>,
+    },
+    /// `from` is the encoded `/bucket/key` of the source object. The copy
+    /// keeps its metadata, unless `metadata` replaces it, with headers such
+    /// as `content-type` or `x-amz-meta-*` ones. The copy only happens if the
+    /// source satisfies `conditions`.
+    CopyObject {
+        from: &'a str,
+        metadata: Option<HeaderMap>,
+        conditions: Option<Conditions>,
+    },
+    /// Copy the bytes `start` to `end`, inclusive, of `from` as a part.
+    UploadPartCopy {
+        from: &'a str,
+        multipart: Multipart<'a>,
+        start: u64,
+        end: u64,
+    },
     CompleteMultipartUpload {
         upload_id: &'a str,
         data: CompleteMultipartUploadData,
@@ -106,6 +234,12 @@ pub enum Command<'a> {
     CreateBucket {
         config: BucketConfiguration,
     },
+    /// List the buckets of the account, sent to the region's endpoint
+    /// itself rather than to a bucket.
+    ListBuckets,
+    DeleteObjects {
+        data: DeleteObjectsData,
+    },
     DeleteBucket,
 }
 
@@ -113,35 +247,63 @@ impl<'a> Command<'a> {
     pub fn http_verb(&self) -> HttpMethod {
         match *self {
             Command::GetObject
+            | Command::GetObjectIf { .. }
+            | Command::GetObjectSse { .. }
             | Command::GetObjectTorrent
             | Command::GetObjectRange { .. }
             | Command::ListBucket { .. }
             | Command::GetBucketLocation
             | Command::GetObjectTagging
             | Command::ListMultipartUploads { .. }
+            | Command::ListParts { .. }
+            | Command::ListBuckets
             | Command::PresignGet { .. } => HttpMethod::Get,
             Command::PutObject { .. }
             | Command::PutObjectTagging { .. }
             | Command::PresignPut { .. }
             | Command::UploadPart { .. }
+            | Command::CopyObject { .. }
+            | Command::UploadPartCopy { .. }
             | Command::CreateBucket { .. } => HttpMethod::Put,
             Command::DeleteObject
             | Command::DeleteObjectTagging
             | Command::AbortMultipartUpload { .. }
+            | Command::PresignDelete { .. }
             | Command::DeleteBucket => HttpMethod::Delete,
-            Command::InitiateMultipartUpload | Command::CompleteMultipartUpload { .. } => {
-                HttpMethod::Post
-            }
-            Command::HeadObject => HttpMethod::Head,
+            Command::InitiateMultipartUpload { .. }
+            | Command::CompleteMultipartUpload { .. }
+            | Command::DeleteObjects { .. } => HttpMethod::Post,
+            Command::HeadObject | Command::HeadObjectSse { .. } => HttpMethod::Head,
         }
     }
 
+    /// Whether `status` is an answer to this command rather than an error,
+    /// even with the `fail-on-err` feature: a precondition of a conditional
+    /// request that failed, or a missing object on HEAD.
+    pub fn is_expected_status(&self, status: u16) -> bool {
+        let conditional = match self {
+            Command::HeadObject | Command::HeadObjectSse { .. } => return status == 404,
+            Command::GetObjectIf { .. } => true,
+            Command::CopyObject {
+                conditions: Some(_),
+                ..
+            } => return status == 412,
+            Command::PutObject {
+                custom_headers: Some(custom_headers),
+                ..
+            } => has_conditions(custom_headers),
+            _ => false,
+        };
+        conditional && (status == 304 || status == 412)
+    }
+
     pub fn content_length(&self) -> usize {
         match &self {
             Command::PutObject { content, .. } => content.len(),
             Command::PutObjectTagging { tags } => tags.len(),
             Command::UploadPart { content, .. } => content.len(),
             Command::CompleteMultipartUpload { data, .. } => data.len(),
+            Command::DeleteObjects { data } => data.to_string().len(),
             Command::CreateBucket { config } => {
                 if let Some(payload) = config.location_constraint_payload() {
                     Vec::from(payload).len()
@@ -156,7 +318,9 @@ impl<'a> Command<'a> {
     pub fn content_type(&self) -> String {
         match self {
             Command::PutObject { content_type, .. } => content_type.to_string(),
-            Command::CompleteMultipartUpload { .. } => "application/xml".into(),
+            Command::CompleteMultipartUpload { .. } | Command::DeleteObjects { .. } => {
+                "application/xml".into()
+            }
             _ => "text/plain".into(),
         }
     }
@@ -166,23 +330,28 @@ impl<'a> Command<'a> {
             Command::PutObject { content, .. } => {
                 let mut sha = Sha256::default();
                 sha.update(content);
-                hex::encode(sha.finalize().as_slice())
+                hex::encode(sha.finalize())
             }
             Command::PutObjectTagging { tags } => {
                 let mut sha = Sha256::default();
                 sha.update(tags.as_bytes());
-                hex::encode(sha.finalize().as_slice())
+                hex::encode(sha.finalize())
             }
             Command::CompleteMultipartUpload { data, .. } => {
                 let mut sha = Sha256::default();
                 sha.update(data.to_string().as_bytes());
-                hex::encode(sha.finalize().as_slice())
+                hex::encode(sha.finalize())
+            }
+            Command::DeleteObjects { data } => {
+                let mut sha = Sha256::default();
+                sha.update(data.to_string().as_bytes());
+                hex::encode(sha.finalize())
             }
             Command::CreateBucket { config } => {
                 if let Some(payload) = config.location_constraint_payload() {
                     let mut sha = Sha256::default();
                     sha.update(payload.as_bytes());
-                    hex::encode(sha.finalize().as_slice())
+                    hex::encode(sha.finalize())
                 } else {
                     EMPTY_PAYLOAD_SHA.into()
                 }
@@ -11,19 +11,25 @@ pub use bucket::Bucket;
 pub use bucket::Tag;
 pub use bucket_ops::BucketConfiguration;
 pub use region::Region;
+pub use retry::RetryPolicy;
 
 #[cfg(feature = "sync")]
 pub mod blocking;
 pub mod bucket;
 pub mod bucket_ops;
+pub mod checksum;
 pub mod command;
 pub mod deserializer;
+pub mod post_policy;
 #[cfg(feature = "with-tokio")]
 pub mod request;
+pub mod retry;
 pub mod serde_types;
 pub mod signing;
+pub mod sse;
 #[cfg(feature = "with-async-std")]
 pub mod surf_request;
+pub mod tls;
 
 pub mod request_trait;
 pub mod utils;
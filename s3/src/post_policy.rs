@@ -0,0 +1,172 @@
+//! Policies of presigned POST requests, letting browsers upload objects
+//! straight to S3 with HTML forms, see `Bucket::presign_post`.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+
+/// Fields of the form `Bucket::presign_post` sets and signs itself.
+const SIGNED_FIELDS: [&str; 7] = [
+    "bucket",
+    "policy",
+    "x-amz-algorithm",
+    "x-amz-credential",
+    "x-amz-date",
+    "x-amz-security-token",
+    "x-amz-signature",
+];
+
+/// A condition the form fields of a presigned POST must meet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PostPolicyCondition {
+    /// `field` must be exactly `value`, it is then part of
+    /// `PresignedPost::fields`. Field names are those of the form, such as
+    /// `Content-Type` or `x-amz-meta-user`.
+    Eq { field: String, value: String },
+    /// `field` must start with `prefix`, an empty one allowing any value.
+    StartsWith { field: String, prefix: String },
+    /// The uploaded file must be between `min` and `max` bytes long.
+    ContentLengthRange { min: u64, max: u64 },
+}
+
+impl PostPolicyCondition {
+    /// Fail on conditions S3 would reject, or that would change the fields
+    /// signing the POST.
+    pub(crate) fn validate(&self) -> Result<()> {
+        match self {
+            PostPolicyCondition::Eq { field, .. }
+            | PostPolicyCondition::StartsWith { field, .. } => {
+                if SIGNED_FIELDS.contains(&field.to_lowercase().as_str()) {
+                    return Err(anyhow!(
+                        "The {} field of presigned POSTs can't be conditioned",
+                        field
+                    ));
+                }
+            }
+            PostPolicyCondition::ContentLengthRange { min, max } => {
+                if min > max {
+                    return Err(anyhow!(
+                        "Invalid content-length-range, {} is above {}",
+                        min,
+                        max
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn to_json(&self) -> String {
+        match self {
+            PostPolicyCondition::Eq { field, value } => format!(
+                "[\"eq\",\"${}\",\"{}\"]",
+                json_escape(field),
+                json_escape(value)
+            ),
+            PostPolicyCondition::StartsWith { field, prefix } => format!(
+                "[\"starts-with\",\"${}\",\"{}\"]",
+                json_escape(field),
+                json_escape(prefix)
+            ),
+            PostPolicyCondition::ContentLengthRange { min, max } => {
+                format!("[\"content-length-range\",{},{}]", min, max)
+            }
+        }
+    }
+}
+
+/// A presigned POST: the HTML form to upload a file with is to be sent to
+/// `url`, with `fields` as hidden inputs followed by the `file` input.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PresignedPost {
+    pub url: String,
+    pub fields: HashMap<String, String>,
+}
+
+/// JSON policy document allowing uploads meeting all of `conditions` until
+/// `expiration`.
+pub(crate) fn policy_document(
+    expiration: &DateTime<Utc>,
+    conditions: &[PostPolicyCondition],
+) -> String {
+    let conditions: Vec<String> = conditions.iter().map(|c| c.to_json()).collect();
+    format!(
+        "{{\"expiration\":\"{}\",\"conditions\":[{}]}}",
+        expiration.format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+        conditions.join(",")
+    )
+}
+
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_policy_document() {
+        let expiration = Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap();
+        let document = policy_document(
+            &expiration,
+            &[
+                PostPolicyCondition::Eq {
+                    field: "bucket".to_string(),
+                    value: "my \"bucket\"".to_string(),
+                },
+                PostPolicyCondition::StartsWith {
+                    field: "key".to_string(),
+                    prefix: "uploads/".to_string(),
+                },
+                PostPolicyCondition::ContentLengthRange { min: 1, max: 1024 },
+            ],
+        );
+        assert_eq!(
+            document,
+            "{\"expiration\":\"2026-01-02T03:04:05.000Z\",\"conditions\":[\
+             [\"eq\",\"$bucket\",\"my \\\"bucket\\\"\"],\
+             [\"starts-with\",\"$key\",\"uploads/\"],\
+             [\"content-length-range\",1,1024]]}"
+        );
+        assert_eq!(json_escape("a\\b\n"), "a\\\\b\\u000a");
+    }
+
+    #[test]
+    fn test_validate_condition() {
+        let eq = |field: &str| PostPolicyCondition::Eq {
+            field: field.to_string(),
+            value: "value".to_string(),
+        };
+        assert!(eq("Content-Type").validate().is_ok());
+        assert!(eq("key").validate().is_ok());
+        assert!(eq("X-Amz-Signature").validate().is_err());
+        assert!(PostPolicyCondition::StartsWith {
+            field: "bucket".to_string(),
+            prefix: String::new(),
+        }
+        .validate()
+        .is_err());
+        assert!(PostPolicyCondition::ContentLengthRange { min: 0, max: 0 }
+            .validate()
+            .is_ok());
+        assert_eq!(
+            PostPolicyCondition::ContentLengthRange { min: 2, max: 1 }
+                .validate()
+                .unwrap_err()
+                .to_string(),
+            "Invalid content-length-range, 2 is above 1"
+        );
+    }
+}
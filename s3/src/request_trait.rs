@@ -1,21 +1,77 @@
 use chrono::{DateTime, Utc};
-use hmac::Mac;
-use hmac::NewMac;
+#[cfg(feature = "with-async-std")]
+use futures::io::AsyncWrite;
 use maybe_async::maybe_async;
+#[cfg(feature = "sync")]
 use std::io::Write;
+#[cfg(feature = "with-tokio")]
+use tokio::io::AsyncWrite;
 use url::Url;
 
 use crate::bucket::Bucket;
-use crate::command::Command;
+use crate::command::{Command, HttpMethod};
 use crate::signing;
 use crate::LONG_DATE;
-use anyhow::anyhow;
 use anyhow::Result;
 use http::header::{
     HeaderName, ACCEPT, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, DATE, HOST, RANGE,
 };
 use http::HeaderMap;
 
+/// Error of a request that took longer than the `Bucket`'s request timeout,
+/// see `Bucket::with_request_timeout`. It can be told apart from other
+/// errors using `anyhow::Error::downcast_ref`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestTimeout {
+    pub timeout: std::time::Duration,
+}
+
+impl std::fmt::Display for RequestTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "request timed out after {:?}", self.timeout)
+    }
+}
+
+impl std::error::Error for RequestTimeout {}
+
+/// Error of a response whose body isn't as long as its `Content-Length`
+/// announced, such as a download cut short by a dropped connection. Like
+/// `RequestTimeout`, it can be told apart using `anyhow::Error::downcast_ref`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentLengthMismatch {
+    pub expected: u64,
+    pub read: u64,
+}
+
+impl std::fmt::Display for ContentLengthMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "read {} bytes of response body, expected {} from Content-Length",
+            self.read, self.expected
+        )
+    }
+}
+
+impl std::error::Error for ContentLengthMismatch {}
+
+/// Check that the `read` bytes are the whole body of the response to
+/// `command`, if it had a `content_length`. Responses to HEAD requests have
+/// the `Content-Length` of the body a GET would have returned.
+pub(crate) fn check_content_length(
+    command: &Command,
+    content_length: Option<&str>,
+    read: u64,
+) -> Result<()> {
+    if let HttpMethod::Head = command.http_verb() {
+        return Ok(());
+    }
+    match content_length.and_then(|length| length.trim().parse().ok()) {
+        Some(expected) if expected != read => Err(ContentLengthMismatch { expected, read }.into()),
+        _ => Ok(()),
+    }
+}
+
 #[maybe_async]
 pub trait Request {
     type Response;
@@ -23,11 +79,21 @@ pub trait Request {
 
     async fn response(&self) -> Result<Self::Response>;
     async fn response_data(&self, etag: bool) -> Result<(Vec<u8>, u16)>;
-    async fn response_data_to_writer<T: Write + Send>(&self, writer: &mut T) -> Result<u16>;
+    /// Stream the response body into `writer`, returns the number of bytes
+    /// written and the status code.
+    #[cfg(any(feature = "with-tokio", feature = "with-async-std"))]
+    async fn response_data_to_writer<T: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut T,
+    ) -> Result<(u64, u16)>;
+    #[cfg(feature = "sync")]
+    fn response_data_to_writer<T: Write + Send>(&self, writer: &mut T) -> Result<(u64, u16)>;
     async fn response_header(&self) -> Result<(Self::HeaderMap, u16)>;
+    /// Response body along with the response headers and the status code.
+    async fn response_data_with_headers(&self) -> Result<(Vec<u8>, Self::HeaderMap, u16)>;
     fn datetime(&self) -> DateTime<Utc>;
     fn bucket(&self) -> Bucket;
-    fn command(&self) -> Command;
+    fn command(&self) -> Command<'_>;
     fn path(&self) -> String;
 
     fn signing_key(&self) -> Result<Vec<u8>> {
@@ -53,6 +119,8 @@ pub trait Request {
             let body = data.to_string();
             // assert_eq!(body, "body".to_string());
             body.as_bytes().to_vec()
+        } else if let Command::DeleteObjects { data } = &self.command() {
+            data.to_string().into_bytes()
         } else if let Command::CreateBucket { config } = &self.command() {
             if let Some(payload) = config.location_constraint_payload() {
                 Vec::from(payload)
@@ -77,29 +145,31 @@ pub trait Request {
     }
 
     fn presigned(&self) -> Result<String> {
-        let expiry = match self.command() {
-            Command::PresignGet { expiry_secs } => expiry_secs,
+        let expiry = self.presign_expiry();
+        let custom_headers = self.presign_headers();
+        Ok(format!(
+            "{}&X-Amz-Signature={}",
+            self.presigned_url_no_sig(expiry, custom_headers.as_ref())?,
+            self.presigned_authorization(custom_headers.as_ref())?
+        ))
+    }
+
+    fn presign_expiry(&self) -> u32 {
+        match self.command() {
+            Command::PresignGet { expiry_secs, .. } => expiry_secs,
             Command::PresignPut { expiry_secs, .. } => expiry_secs,
+            Command::PresignDelete { expiry_secs } => expiry_secs,
             _ => unreachable!(),
-        };
-
-        #[allow(clippy::collapsible_match)]
-        if let Command::PresignPut { custom_headers, .. } = self.command() {
-            if let Some(custom_headers) = custom_headers {
-                let authorization = self.presigned_authorization(Some(&custom_headers))?;
-                return Ok(format!(
-                    "{}&X-Amz-Signature={}",
-                    self.presigned_url_no_sig(expiry, Some(&custom_headers))?,
-                    authorization
-                ));
-            }
         }
+    }
 
-        Ok(format!(
-            "{}&X-Amz-Signature={}",
-            self.presigned_url_no_sig(expiry, None)?,
-            self.presigned_authorization(None)?
-        ))
+    /// Headers a presigned URL is signed for, besides `host`.
+    fn presign_headers(&self) -> Option<HeaderMap> {
+        match self.command() {
+            Command::PresignGet { custom_headers, .. }
+            | Command::PresignPut { custom_headers, .. } => custom_headers,
+            _ => None,
+        }
     }
 
     fn presigned_authorization(&self, custom_headers: Option<&HeaderMap>) -> Result<String> {
@@ -113,70 +183,44 @@ pub trait Request {
         }
         let canonical_request = self.presigned_canonical_request(&headers)?;
         let string_to_sign = self.string_to_sign(&canonical_request);
-        let mut hmac =
-            signing::HmacSha256::new_varkey(&self.signing_key()?).map_err(|e| anyhow! {"{}",e})?;
-        hmac.update(string_to_sign.as_bytes());
-        let signature = hex::encode(hmac.finalize().into_bytes());
+        let signature = signing::sign(&self.signing_key()?, &string_to_sign);
         // let signed_header = signing::signed_header_string(&headers);
         Ok(signature)
     }
 
     fn presigned_canonical_request(&self, headers: &HeaderMap) -> Result<String> {
-        let expiry = match self.command() {
-            Command::PresignGet { expiry_secs } => expiry_secs,
-            Command::PresignPut { expiry_secs, .. } => expiry_secs,
-            _ => unreachable!(),
-        };
-
-        #[allow(clippy::collapsible_match)]
-        if let Command::PresignPut { custom_headers, .. } = self.command() {
-            if let Some(custom_headers) = custom_headers {
-                return Ok(signing::canonical_request(
-                    &self.command().http_verb().to_string(),
-                    &self.presigned_url_no_sig(expiry, Some(&custom_headers))?,
-                    headers,
-                    "UNSIGNED-PAYLOAD",
-                ));
-            }
-        }
-
-        Ok(signing::canonical_request(
+        signing::canonical_request(
             &self.command().http_verb().to_string(),
-            &self.presigned_url_no_sig(expiry, None)?,
+            &self.presigned_url_no_sig(self.presign_expiry(), self.presign_headers().as_ref())?,
             headers,
             "UNSIGNED-PAYLOAD",
-        ))
+        )
     }
 
     fn presigned_url_no_sig(&self, expiry: u32, custom_headers: Option<&HeaderMap>) -> Result<Url> {
         let bucket = self.bucket();
-        let token = if let Some(security_token) = bucket.security_token() {
-            Some(security_token)
-        } else if let Some(session_token) = bucket.session_token() {
-            Some(session_token)
-        } else {
-            None
+        let token = bucket.security_token().or_else(|| bucket.session_token());
+        let url = self.url();
+        let query_params = signing::authorization_query_params_no_sig(
+            &self.bucket().access_key().unwrap(),
+            &self.datetime(),
+            &self.bucket().region(),
+            expiry,
+            custom_headers,
+            token,
+        )?;
+        // The URL may already have a query, of the bucket or of the request
+        let query_params = match url.query() {
+            Some(_) => query_params.replacen('?', "&", 1),
+            None => query_params,
         };
-        let url = Url::parse(&format!(
-            "{}{}",
-            self.url(),
-            &signing::authorization_query_params_no_sig(
-                &self.bucket().access_key().unwrap(),
-                &self.datetime(),
-                &self.bucket().region(),
-                expiry,
-                custom_headers,
-                token
-            )?
-        ))?;
-
-        Ok(url)
+        Ok(Url::parse(&format!("{}{}", url, query_params))?)
     }
 
     fn url(&self) -> Url {
         let mut url_str = self.bucket().url();
 
-        if let Command::CreateBucket { .. } = self.command() {
+        if let Command::CreateBucket { .. } | Command::ListBuckets = self.command() {
             return Url::parse(&url_str).unwrap();
         }
 
@@ -188,26 +232,30 @@ pub trait Request {
 
         url_str.push('/');
 
-        url_str.push_str(&signing::uri_encode(&path, true));
+        url_str.push_str(&signing::aws_uri_encode(&path, false));
 
         // Append to url_path
         #[allow(clippy::collapsible_match)]
         match self.command() {
-            Command::InitiateMultipartUpload | Command::ListMultipartUploads { .. } => {
+            Command::InitiateMultipartUpload { .. } | Command::ListMultipartUploads { .. } => {
                 url_str.push_str("?uploads")
             }
-            Command::AbortMultipartUpload { upload_id } => {
+            Command::AbortMultipartUpload { upload_id } | Command::ListParts { upload_id, .. } => {
                 url_str.push_str(&format!("?uploadId={}", upload_id))
             }
             Command::CompleteMultipartUpload { upload_id, .. } => {
                 url_str.push_str(&format!("?uploadId={}", upload_id))
             }
             Command::GetObjectTorrent => url_str.push_str("?torrent"),
+            Command::DeleteObjects { .. } => url_str.push_str("?delete"),
             Command::PutObject { multipart, .. } => {
                 if let Some(multipart) = multipart {
                     url_str.push_str(&multipart.query_string())
                 }
             }
+            Command::UploadPartCopy { multipart, .. } => {
+                url_str.push_str(&multipart.query_string())
+            }
             _ => {}
         }
 
@@ -219,6 +267,16 @@ pub trait Request {
             url.query_pairs_mut().append_pair(key, value);
         }
 
+        if let Command::PresignGet {
+            custom_queries: Some(custom_queries),
+            ..
+        } = self.command()
+        {
+            for (key, value) in &custom_queries {
+                url.query_pairs_mut().append_pair(key, value);
+            }
+        }
+
         // println!("{}", url_str);
 
         if let Command::ListBucket {
@@ -252,7 +310,7 @@ pub trait Request {
                 max_uploads,
             } => {
                 let mut query_pairs = url.query_pairs_mut();
-                delimiter.map(|d| query_pairs.append_pair("delimiter", &d));
+                delimiter.map(|d| query_pairs.append_pair("delimiter", d));
                 if let Some(prefix) = prefix {
                     query_pairs.append_pair("prefix", prefix);
                 }
@@ -263,6 +321,13 @@ pub trait Request {
                     query_pairs.append_pair("max-uploads", max_uploads.to_string().as_str());
                 }
             }
+            Command::ListParts {
+                part_number_marker: Some(part_number_marker),
+                ..
+            } => {
+                url.query_pairs_mut()
+                    .append_pair("part-number-marker", &part_number_marker.to_string());
+            }
             Command::PutObjectTagging { .. }
             | Command::GetObjectTagging
             | Command::DeleteObjectTagging => {
@@ -274,7 +339,7 @@ pub trait Request {
         url
     }
 
-    fn canonical_request(&self, headers: &HeaderMap) -> String {
+    fn canonical_request(&self, headers: &HeaderMap) -> Result<String> {
         signing::canonical_request(
             &self.command().http_verb().to_string(),
             &self.url(),
@@ -284,12 +349,9 @@ pub trait Request {
     }
 
     fn authorization(&self, headers: &HeaderMap) -> Result<String> {
-        let canonical_request = self.canonical_request(headers);
+        let canonical_request = self.canonical_request(headers)?;
         let string_to_sign = self.string_to_sign(&canonical_request);
-        let mut hmac =
-            signing::HmacSha256::new_varkey(&self.signing_key()?).map_err(|e| anyhow! {"{}",e})?;
-        hmac.update(string_to_sign.as_bytes());
-        let signature = hex::encode(hmac.finalize().into_bytes());
+        let signature = signing::sign(&self.signing_key()?, &string_to_sign);
         let signed_header = signing::signed_header_string(headers);
         Ok(signing::authorization_header(
             &self.bucket().access_key().unwrap(),
@@ -320,8 +382,19 @@ pub trait Request {
         match self.command() {
             Command::ListBucket { .. } => {}
             Command::GetObject => {}
+            Command::GetObjectIf { .. } => {}
+            Command::GetObjectSse { .. } => {}
             Command::GetObjectTagging => {}
             Command::GetBucketLocation => {}
+            Command::ListBuckets => {}
+            // Copies have no body, and a Content-Type would be the copy's
+            // rather than the source's: only the one in `metadata` is sent
+            Command::CopyObject { .. } => {
+                headers.insert(
+                    CONTENT_LENGTH,
+                    self.command().content_length().to_string().parse().unwrap(),
+                );
+            }
             _ => {
                 headers.insert(
                     CONTENT_LENGTH,
@@ -330,14 +403,19 @@ pub trait Request {
                 headers.insert(CONTENT_TYPE, self.command().content_type().parse().unwrap());
             }
         }
-        headers.insert(
-            HeaderName::from_static("x-amz-content-sha256"),
-            sha256.parse().unwrap(),
-        );
-        headers.insert(
-            HeaderName::from_static("x-amz-date"),
-            self.long_date().parse().unwrap(),
-        );
+        // Anonymous requests are sent bare, without anything the signature
+        // would need
+        let anonymous = self.bucket().is_anonymous();
+        if !anonymous {
+            headers.insert(
+                HeaderName::from_static("x-amz-content-sha256"),
+                sha256.parse().unwrap(),
+            );
+            headers.insert(
+                HeaderName::from_static("x-amz-date"),
+                self.long_date().parse().unwrap(),
+            );
+        }
 
         if let Some(session_token) = self.bucket().session_token() {
             headers.insert(
@@ -365,6 +443,14 @@ pub trait Request {
                 HeaderName::from_static("content-md5"),
                 hash.parse().unwrap(),
             );
+        } else if let Command::DeleteObjects { data } = self.command() {
+            // Required by DeleteObjects
+            let digest = md5::compute(data.to_string());
+            let hash = base64::encode(digest.as_ref());
+            headers.insert(
+                HeaderName::from_static("content-md5"),
+                hash.parse().unwrap(),
+            );
         } else if let Command::UploadPart { content, .. } = self.command() {
             let digest = md5::compute(content);
             let hash = base64::encode(digest.as_ref());
@@ -378,6 +464,20 @@ pub trait Request {
                 "application/octet-stream".to_string().parse().unwrap(),
             );
         // headers.insert(header::ACCEPT_CHARSET, HeaderValue::from_str("UTF-8")?);
+        } else if let Command::GetObjectIf { conditions } = self.command() {
+            headers.insert(
+                ACCEPT,
+                "application/octet-stream".to_string().parse().unwrap(),
+            );
+            headers.extend(conditions.headers()?);
+        } else if let Command::GetObjectSse { sse_headers } = self.command() {
+            headers.insert(
+                ACCEPT,
+                "application/octet-stream".to_string().parse().unwrap(),
+            );
+            headers.extend(sse_headers.clone());
+        } else if let Command::HeadObjectSse { sse_headers } = self.command() {
+            headers.extend(sse_headers.clone());
         } else if let Command::GetObjectRange { start, end } = self.command() {
             headers.insert(
                 ACCEPT,
@@ -393,10 +493,89 @@ pub trait Request {
             headers.insert(RANGE, range.parse().unwrap());
         } else if let Command::CreateBucket { ref config } = self.command() {
             config.add_headers(&mut headers)?;
+        } else if let Command::CopyObject {
+            from, conditions, ..
+        } = self.command()
+        {
+            headers.insert(
+                HeaderName::from_static("x-amz-copy-source"),
+                from.parse().unwrap(),
+            );
+            if let Some(conditions) = conditions {
+                headers.extend(conditions.copy_source_headers()?);
+            }
+        } else if let Command::UploadPartCopy {
+            from, start, end, ..
+        } = self.command()
+        {
+            headers.insert(
+                HeaderName::from_static("x-amz-copy-source"),
+                from.parse().unwrap(),
+            );
+            headers.insert(
+                HeaderName::from_static("x-amz-copy-source-range"),
+                format!("bytes={}-{}", start, end).parse().unwrap(),
+            );
+        }
+
+        if let Some(algorithm) = self.bucket().checksum_algorithm() {
+            match self.command() {
+                Command::PutObject { content, .. } => {
+                    headers.insert(algorithm.header(), algorithm.checksum(content).parse()?);
+                }
+                Command::InitiateMultipartUpload { .. } => {
+                    headers.insert(
+                        HeaderName::from_static("x-amz-checksum-algorithm"),
+                        algorithm.name().parse()?,
+                    );
+                }
+                Command::GetObject | Command::GetObjectSse { .. } => {
+                    headers.insert(
+                        HeaderName::from_static("x-amz-checksum-mode"),
+                        "ENABLED".parse()?,
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        if let Command::PutObject {
+            custom_headers: Some(custom_headers),
+            ..
+        } = self.command()
+        {
+            for (k, v) in custom_headers.iter() {
+                if !is_computed_header(k) {
+                    headers.insert(k.clone(), v.clone());
+                }
+            }
+        } else if let Command::CopyObject {
+            metadata: Some(metadata),
+            ..
+        } = self.command()
+        {
+            headers.insert(
+                HeaderName::from_static("x-amz-metadata-directive"),
+                "REPLACE".parse().unwrap(),
+            );
+            for (k, v) in metadata.iter() {
+                if !is_computed_header(k) {
+                    headers.insert(k.clone(), v.clone());
+                }
+            }
+        } else if let Command::InitiateMultipartUpload {
+            metadata: Some(metadata),
+        } = self.command()
+        {
+            for (k, v) in metadata.iter() {
+                if !is_computed_header(k) {
+                    headers.insert(k.clone(), v.clone());
+                }
+            }
         }
 
         // This must be last, as it signs the other headers, omitted if no secret key is provided
-        if self.bucket().secret_key().is_some() {
+        if !anonymous && self.bucket().secret_key().is_some() {
             let authorization = self.authorization(&headers)?;
             headers.insert(AUTHORIZATION, authorization.parse().unwrap());
         }
@@ -412,3 +591,37 @@ pub trait Request {
         Ok(headers)
     }
 }
+
+/// Headers derived from the request itself, that callers can't override.
+fn is_computed_header(name: &HeaderName) -> bool {
+    [HOST, CONTENT_LENGTH, AUTHORIZATION, DATE].contains(name)
+        || name == "x-amz-content-sha256"
+        || name == "x-amz-date"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_content_length() {
+        let command = Command::GetObject;
+        assert!(check_content_length(&command, Some("3"), 3).is_ok());
+        assert!(check_content_length(&command, None, 3).is_ok());
+        assert!(check_content_length(&command, Some("invalid"), 3).is_ok());
+        assert!(check_content_length(&Command::HeadObject, Some("3"), 0).is_ok());
+
+        let error = check_content_length(&command, Some("3"), 2).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ContentLengthMismatch>(),
+            Some(&ContentLengthMismatch {
+                expected: 3,
+                read: 2
+            })
+        );
+        assert_eq!(
+            error.to_string(),
+            "read 2 bytes of response body, expected 3 from Content-Length"
+        );
+    }
+}
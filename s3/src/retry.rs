@@ -0,0 +1,208 @@
+//! Retries of requests S3 failed to serve, such as `503 SlowDown`.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::command::HttpMethod;
+
+/// How a `Bucket` retries requests answered with a 5xx status, including
+/// `503 SlowDown`, or with `429 Too Many Requests`, and requests that failed
+/// to connect or lost their connection, such as on a reset. Requests that
+/// timed out aren't retried.
+///
+/// Only idempotent requests are retried: GET, HEAD, PUT, including multipart
+/// part uploads, and DELETE. The delay between attempts doubles from
+/// `base_delay`, unless S3 asked for a specific one using `Retry-After`, and
+/// never exceeds `max_delay`.
+///
+/// Buckets don't retry requests unless given a policy with
+/// `Bucket::with_retries`.
+///
+/// # Example
+///
+/// ```no_run
+/// use s3::bucket::Bucket;
+/// use s3::creds::Credentials;
+/// use s3::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let region = "us-east-1".parse().unwrap();
+/// let credentials = Credentials::default().unwrap();
+/// let bucket = Bucket::new("rust-s3-test", region, credentials)
+///     .unwrap()
+///     .with_retries(RetryPolicy {
+///         max_attempts: 5,
+///         base_delay: Duration::from_millis(200),
+///         ..RetryPolicy::default()
+///     });
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    /// Number of times a request is sent before giving up, including the
+    /// first one. `1` disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub base_delay: Duration,
+    /// Upper bound of any delay, including the ones asked for by S3.
+    pub max_delay: Duration,
+    /// Wait a random duration between half and all of the computed delay,
+    /// so that concurrent clients don't all retry at once.
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, 100ms apart then 200ms, with jitter.
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(20),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Send every request once.
+    pub fn never() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// How long to wait before retrying a request that got `status` on its
+    /// `attempt`th try, or `None` if it should not be retried.
+    pub(crate) fn retry_delay(
+        &self,
+        attempt: u32,
+        method: &HttpMethod,
+        status: u16,
+        retry_after: Option<&str>,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts || !method.is_idempotent() || !is_retryable(status) {
+            return None;
+        }
+        if let Some(delay) = retry_after.and_then(parse_retry_after) {
+            return Some(delay.min(self.max_delay));
+        }
+        Some(self.backoff(attempt))
+    }
+
+    /// How long to wait before retrying a request whose `attempt`th try
+    /// failed without a response, or `None` if it should not be retried.
+    pub(crate) fn network_error_delay(
+        &self,
+        attempt: u32,
+        method: &HttpMethod,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts || !method.is_idempotent() {
+            return None;
+        }
+        Some(self.backoff(attempt))
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .checked_mul(1 << (attempt - 1).min(31))
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        if self.jitter {
+            let half = delay / 2;
+            let nanos = half.as_nanos() as u64;
+            let random = RandomState::new().build_hasher().finish();
+            half + Duration::from_nanos(random.checked_rem(nanos).unwrap_or(0))
+        } else {
+            delay
+        }
+    }
+}
+
+fn is_retryable(status: u16) -> bool {
+    status == 429 || (500..600).contains(&status)
+}
+
+/// `Retry-After` is either a number of seconds or an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = RetryPolicy {
+            jitter: false,
+            ..RetryPolicy::default()
+        };
+        let get = HttpMethod::Get;
+        assert_eq!(
+            policy.retry_delay(1, &get, 503, None),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            policy.retry_delay(2, &get, 500, None),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(policy.retry_delay(3, &get, 503, None), None);
+        assert_eq!(
+            policy.retry_delay(1, &get, 429, Some("2")),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            policy.retry_delay(1, &get, 503, Some("3600")),
+            Some(policy.max_delay)
+        );
+        assert_eq!(policy.retry_delay(1, &get, 404, None), None);
+        assert_eq!(policy.retry_delay(1, &HttpMethod::Post, 503, None), None);
+        assert_eq!(RetryPolicy::never().retry_delay(1, &get, 503, None), None);
+
+        let delay = RetryPolicy::default()
+            .retry_delay(2, &get, 503, None)
+            .unwrap();
+        assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_network_error_delay() {
+        let policy = RetryPolicy {
+            jitter: false,
+            ..RetryPolicy::default()
+        };
+        assert_eq!(
+            policy.network_error_delay(1, &HttpMethod::Put),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            policy.network_error_delay(2, &HttpMethod::Delete),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(policy.network_error_delay(3, &HttpMethod::Get), None);
+        assert_eq!(policy.network_error_delay(1, &HttpMethod::Post), None);
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after(" 5 "), Some(Duration::from_secs(5)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::from_secs(0))
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+}
@@ -1,7 +1,10 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
 #[derive(Deserialize, Debug)]
 pub struct InitiateMultipartUploadResponse {
     #[serde(rename = "Bucket")]
-    bucket: String,
+    pub bucket: String,
     #[serde(rename = "Key")]
     pub key: String,
     #[serde(rename = "UploadId")]
@@ -19,6 +22,32 @@ pub struct Owner {
     pub id: String,
 }
 
+/// The parsed result of a `ListBuckets` request, see `Bucket::list_buckets`
+#[derive(Deserialize, Debug, Clone)]
+pub struct ListAllMyBucketsResult {
+    #[serde(rename = "Owner")]
+    /// Owner of the buckets.
+    pub owner: Option<Owner>,
+    #[serde(rename = "Buckets")]
+    pub buckets: Buckets,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Buckets {
+    #[serde(rename = "Bucket", default)]
+    pub bucket: Vec<BucketInfo>,
+}
+
+/// An individual bucket in a `ListAllMyBucketsResult`
+#[derive(Deserialize, Debug, Clone)]
+pub struct BucketInfo {
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "CreationDate")]
+    /// Date and time the bucket was created.
+    pub creation_date: String,
+}
+
 /// An individual object in a `ListBucketResult`
 #[derive(Deserialize, Debug, Clone)]
 pub struct Object {
@@ -81,11 +110,11 @@ impl fmt::Display for CompleteMultipartUploadData {
 
 impl CompleteMultipartUploadData {
     pub fn len(&self) -> usize {
-        self.to_string().as_bytes().len()
+        self.to_string().len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.to_string().as_bytes().len() == 0
+        self.to_string().len() == 0
     }
 }
 
@@ -94,12 +123,99 @@ pub struct CompleteMultipartUploadData {
     pub parts: Vec<Part>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// Body of a `DeleteObjects` request, for up to 1000 keys
+#[derive(Debug, Clone)]
+pub struct DeleteObjectsData {
+    pub keys: Vec<String>,
+}
+
+impl fmt::Display for DeleteObjectsData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<Delete>")?;
+        for key in &self.keys {
+            write!(f, "<Object><Key>{}</Key></Object>", xml_escape(key))?;
+        }
+        write!(f, "</Delete>")
+    }
+}
+
+pub(crate) fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// The parsed result of `DeleteObjects` requests
+#[derive(Debug, Clone, Default)]
+pub struct DeleteResult {
+    /// Objects that were deleted, or didn't exist.
+    pub deleted: Vec<DeletedObject>,
+    /// Objects that couldn't be deleted, along with why.
+    pub errors: Vec<DeleteError>,
+}
+
+impl DeleteResult {
+    /// Keys of the objects that couldn't be deleted, to retry deleting them
+    /// with `Bucket::delete_objects`.
+    pub fn failed_keys(&self) -> Vec<String> {
+        self.errors.iter().map(|error| error.key.clone()).collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeletedObject {
+    pub key: String,
+    pub version_id: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeleteError {
+    pub key: String,
+    /// Error code, such as `AccessDenied`.
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CompleteMultipartUploadResult {
+    #[serde(rename = "ETag")]
+    pub etag: String,
+    #[serde(rename = "ChecksumCRC32C")]
+    pub checksum_crc32c: Option<String>,
+    #[serde(rename = "ChecksumSHA256")]
+    pub checksum_sha256: Option<String>,
+}
+
+/// The parsed result of a copy, either a `CopyObjectResult` or a
+/// `CopyPartResult`.
+#[derive(Deserialize, Debug)]
+pub struct CopyObjectResult {
+    #[serde(rename = "ETag")]
+    pub etag: String,
+    #[serde(rename = "LastModified")]
+    pub last_modified: Option<String>,
+    #[serde(rename = "ChecksumCRC32C")]
+    pub checksum_crc32c: Option<String>,
+    #[serde(rename = "ChecksumSHA256")]
+    pub checksum_sha256: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Part {
     #[serde(rename = "PartNumber")]
     pub part_number: u32,
     #[serde(rename = "ETag")]
     pub etag: String,
+    #[serde(rename = "ChecksumCRC32C", skip_serializing_if = "Option::is_none")]
+    pub checksum_crc32c: Option<String>,
+    #[serde(rename = "ChecksumSHA256", skip_serializing_if = "Option::is_none")]
+    pub checksum_sha256: Option<String>,
+    /// Size in bytes, when known, checked by
+    /// `Bucket::complete_multipart_upload`.
+    #[serde(rename = "Size", default, skip_serializing)]
+    pub size: Option<u64>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -296,6 +412,66 @@ pub struct HeadObjectResult {
     pub website_redirect_location: Option<String>,
 }
 
+/// The parsed result of listing the parts uploaded for a multipart upload
+#[derive(Deserialize, Debug, Clone)]
+pub struct ListPartsResult {
+    #[serde(rename = "Bucket")]
+    /// Name of the bucket.
+    pub bucket: String,
+    #[serde(rename = "Key")]
+    /// Key the object will be stored at.
+    pub key: String,
+    #[serde(rename = "UploadId")]
+    /// The identifier of the upload
+    pub upload_id: String,
+    #[serde(rename = "NextPartNumberMarker", default)]
+    /// When the response is truncated, the part number to start the next page of the listing
+    /// after.
+    pub next_part_number_marker: Option<u32>,
+    #[serde(
+        rename = "IsTruncated",
+        deserialize_with = "super::deserializer::bool_deserializer"
+    )]
+    /// Specifies whether (true) or not (false) all of the parts were returned.
+    pub is_truncated: bool,
+    #[serde(rename = "Part", default)]
+    /// Number and ETag of each part uploaded so far.
+    pub parts: Vec<Part>,
+}
+
+/// Object returned by `Bucket::get_object_with_headers`, along with the
+/// response headers.
+#[derive(Debug, Clone)]
+pub struct GetObjectResult {
+    /// Body of the object.
+    pub data: Vec<u8>,
+    /// A standard MIME type describing the format of the object data.
+    pub content_type: Option<String>,
+    /// Size of the body in bytes.
+    pub content_length: Option<u64>,
+    /// An ETag is an opaque identifier assigned by a web server to a specific version of a resource found at a URL.
+    pub etag: Option<String>,
+    /// Last modified date of the object
+    pub last_modified: Option<DateTime<Utc>>,
+    /// User metadata stored with the object, from the `x-amz-meta-*` headers without their prefix.
+    pub metadata: HashMap<String, String>,
+    /// Bytes of the object in `data`, for range requests.
+    pub content_range: Option<ContentRange>,
+    /// All of the response headers.
+    pub headers: http::HeaderMap,
+}
+
+/// `Content-Range` of a response to a range request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentRange {
+    /// First byte of the range.
+    pub start: u64,
+    /// Last byte of the range, inclusive.
+    pub end: u64,
+    /// Size of the whole object, if known.
+    pub total_size: Option<u64>,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct AwsError {
     #[serde(rename = "Code")]
@@ -305,3 +481,96 @@ pub struct AwsError {
     #[serde(rename = "RequestId")]
     pub request_id: String,
 }
+
+/// Error S3 answered a request with, parsed from the XML body of the response.
+///
+/// Operations failing with a status S3 didn't explain, such as a `HEAD`
+/// request which has no body, get an empty `code` and `message`. It can be
+/// told apart from other errors using `anyhow::Error::downcast_ref`:
+///
+/// ```
+/// use s3::serde_types::S3Error;
+///
+/// fn should_slow_down(error: &anyhow::Error) -> bool {
+///     matches!(error.downcast_ref::<S3Error>(), Some(e) if e.code == "SlowDown")
+/// }
+/// ```
+///
+/// Without the `fail-on-err` feature, object operations such as
+/// `Bucket::get_object` return the status and body of error responses
+/// rather than failing, `check_response` turns them into this error:
+///
+/// ```no_run
+/// use s3::bucket::Bucket;
+/// use s3::creds::Credentials;
+/// use s3::serde_types::S3Error;
+/// use anyhow::Result;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let bucket = Bucket::new("rust-s3-test", "us-east-1".parse()?, Credentials::default()?)?;
+/// match S3Error::check_response(bucket.get_object("/test.file").await?) {
+///     Ok(data) => println!("{} bytes", data.len()),
+///     Err(e) if e.code == "NoSuchKey" => println!("No such file"),
+///     Err(e) => return Err(e.into()),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct S3Error {
+    /// Error code, such as `NoSuchKey`, `AccessDenied` or `SlowDown`.
+    #[serde(rename = "Code", default)]
+    pub code: String,
+    #[serde(rename = "Message", default)]
+    pub message: String,
+    #[serde(rename = "RequestId")]
+    pub request_id: Option<String>,
+    /// Bucket or object the error is about.
+    #[serde(rename = "Resource")]
+    pub resource: Option<String>,
+    /// HTTP status code of the response.
+    #[serde(skip)]
+    pub status: u16,
+}
+
+impl S3Error {
+    /// Parse the error S3 answered with `status` and `body`, falling back to
+    /// an error without code when `body` isn't S3 error XML.
+    pub fn from_response(status: u16, body: &[u8]) -> S3Error {
+        let error = std::str::from_utf8(body)
+            .ok()
+            .and_then(|body| serde_xml_rs::from_str::<S3Error>(body).ok());
+        S3Error {
+            status,
+            ..error.unwrap_or_default()
+        }
+    }
+
+    /// The body of the `(body, status)` an object operation returned, or the
+    /// error S3 answered with if `status` is `400` or above.
+    pub fn check_response((body, status): (Vec<u8>, u16)) -> Result<Vec<u8>, S3Error> {
+        if status >= 400 {
+            return Err(S3Error::from_response(status, &body));
+        }
+        Ok(body)
+    }
+}
+
+impl fmt::Display for S3Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.code.is_empty() {
+            return write!(f, "S3 answered with code {}", self.status);
+        }
+        write!(f, "{} ({}): {}", self.code, self.status, self.message)?;
+        if let Some(resource) = &self.resource {
+            write!(f, ", resource {}", resource)?;
+        }
+        if let Some(request_id) = &self.request_id {
+            write!(f, ", request id {}", request_id)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for S3Error {}
@@ -0,0 +1,190 @@
+//! Server-side encryption of uploaded objects, see
+//! `Bucket::put_object_with_sse` and `Bucket::get_object_with_sse`.
+
+use anyhow::{anyhow, Result};
+use http::header::HeaderName;
+use http::HeaderMap;
+
+const SSE: &str = "x-amz-server-side-encryption";
+const SSE_KMS_KEY_ID: &str = "x-amz-server-side-encryption-aws-kms-key-id";
+const SSE_CUSTOMER_ALGORITHM: &str = "x-amz-server-side-encryption-customer-algorithm";
+const SSE_CUSTOMER_KEY: &str = "x-amz-server-side-encryption-customer-key";
+const SSE_CUSTOMER_KEY_MD5: &str = "x-amz-server-side-encryption-customer-key-md5";
+
+/// How S3 encrypts an object at rest.
+#[derive(Clone, Eq, PartialEq)]
+pub enum ServerSideEncryption {
+    /// SSE-S3, with keys managed by S3.
+    Aes256,
+    /// SSE-KMS with the KMS key `key_id`, the AWS managed `aws/s3` key if
+    /// `None`.
+    AwsKms { key_id: Option<String> },
+    /// Dual-layer SSE-KMS, otherwise the same as `AwsKms`.
+    AwsKmsDsse { key_id: Option<String> },
+    /// SSE-C, with a 256-bit `key` S3 doesn't keep. Reading the object back
+    /// needs the same key, see `Bucket::get_object_with_sse`.
+    Customer { key: Vec<u8> },
+}
+
+/// Keeps customer keys out of logs.
+impl std::fmt::Debug for ServerSideEncryption {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ServerSideEncryption::Aes256 => write!(f, "Aes256"),
+            ServerSideEncryption::AwsKms { key_id } => {
+                f.debug_struct("AwsKms").field("key_id", key_id).finish()
+            }
+            ServerSideEncryption::AwsKmsDsse { key_id } => f
+                .debug_struct("AwsKmsDsse")
+                .field("key_id", key_id)
+                .finish(),
+            ServerSideEncryption::Customer { .. } => {
+                f.debug_struct("Customer").field("key", &"***").finish()
+            }
+        }
+    }
+}
+
+impl ServerSideEncryption {
+    /// Value of `x-amz-server-side-encryption`, or of its `-customer-algorithm`
+    /// counterpart for SSE-C.
+    pub fn algorithm(&self) -> &'static str {
+        match self {
+            ServerSideEncryption::Aes256 | ServerSideEncryption::Customer { .. } => "AES256",
+            ServerSideEncryption::AwsKms { .. } => "aws:kms",
+            ServerSideEncryption::AwsKmsDsse { .. } => "aws:kms:dsse",
+        }
+    }
+
+    /// Request headers selecting this encryption. Fails for SSE-C keys that
+    /// aren't 32 bytes long.
+    pub fn headers(&self) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        match self {
+            ServerSideEncryption::Aes256 => {
+                headers.insert(HeaderName::from_static(SSE), self.algorithm().parse()?);
+            }
+            ServerSideEncryption::AwsKms { key_id }
+            | ServerSideEncryption::AwsKmsDsse { key_id } => {
+                headers.insert(HeaderName::from_static(SSE), self.algorithm().parse()?);
+                if let Some(key_id) = key_id {
+                    headers.insert(HeaderName::from_static(SSE_KMS_KEY_ID), key_id.parse()?);
+                }
+            }
+            ServerSideEncryption::Customer { key } => {
+                if key.len() != 32 {
+                    return Err(anyhow!(
+                        "SSE-C keys must be 256 bits long, got {} bits",
+                        key.len() * 8
+                    ));
+                }
+                headers.insert(
+                    HeaderName::from_static(SSE_CUSTOMER_ALGORITHM),
+                    self.algorithm().parse()?,
+                );
+                headers.insert(
+                    HeaderName::from_static(SSE_CUSTOMER_KEY),
+                    base64::encode(key).parse()?,
+                );
+                headers.insert(
+                    HeaderName::from_static(SSE_CUSTOMER_KEY_MD5),
+                    base64::encode(md5::compute(key).as_ref()).parse()?,
+                );
+            }
+        }
+        Ok(headers)
+    }
+
+    /// Request headers reading an object encrypted this way: the key for
+    /// SSE-C, none otherwise, as S3 decrypts other objects by itself.
+    pub fn read_headers(&self) -> Result<HeaderMap> {
+        match self {
+            ServerSideEncryption::Customer { .. } => self.headers(),
+            _ => Ok(HeaderMap::new()),
+        }
+    }
+
+    /// Fail unless the `headers` of a successful response confirm the object
+    /// was encrypted this way, rather than trusting servers that ignore
+    /// encryption headers they don't support.
+    pub(crate) fn check_response(&self, headers: &HeaderMap) -> Result<()> {
+        let (header, expected) = match self {
+            ServerSideEncryption::Customer { .. } => (SSE_CUSTOMER_ALGORITHM, self.algorithm()),
+            _ => (SSE, self.algorithm()),
+        };
+        let returned = headers.get(header).and_then(|value| value.to_str().ok());
+        if returned != Some(expected) {
+            return Err(anyhow!(
+                "Requested {} server-side encryption, the response has {} {:?}",
+                expected,
+                header,
+                returned
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_headers() {
+        let headers = ServerSideEncryption::Aes256.headers().unwrap();
+        assert_eq!(headers[SSE], "AES256");
+        assert_eq!(headers.len(), 1);
+
+        let kms = ServerSideEncryption::AwsKms {
+            key_id: Some("alias/my-key".to_string()),
+        };
+        let headers = kms.headers().unwrap();
+        assert_eq!(headers[SSE], "aws:kms");
+        assert_eq!(headers[SSE_KMS_KEY_ID], "alias/my-key");
+
+        let dsse = ServerSideEncryption::AwsKmsDsse { key_id: None };
+        let headers = dsse.headers().unwrap();
+        assert_eq!(headers[SSE], "aws:kms:dsse");
+        assert!(!headers.contains_key(SSE_KMS_KEY_ID));
+
+        let customer = ServerSideEncryption::Customer { key: vec![0; 32] };
+        let headers = customer.headers().unwrap();
+        assert_eq!(headers[SSE_CUSTOMER_ALGORITHM], "AES256");
+        assert_eq!(
+            headers[SSE_CUSTOMER_KEY],
+            "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA="
+        );
+        assert_eq!(headers[SSE_CUSTOMER_KEY_MD5], "cLyPS3KoaSFGi/joRB3OUQ==");
+        assert!(!headers.contains_key(SSE));
+        assert_eq!(format!("{:?}", customer), "Customer { key: \"***\" }");
+
+        assert!(ServerSideEncryption::Customer { key: vec![0; 16] }
+            .headers()
+            .is_err());
+
+        assert_eq!(
+            customer.read_headers().unwrap(),
+            customer.headers().unwrap()
+        );
+        assert!(kms.read_headers().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sse_check_response() {
+        let mut headers = HeaderMap::new();
+        headers.insert(SSE, "aws:kms".parse().unwrap());
+        let kms = ServerSideEncryption::AwsKms { key_id: None };
+        assert!(kms.check_response(&headers).is_ok());
+        assert_eq!(
+            ServerSideEncryption::Aes256
+                .check_response(&headers)
+                .unwrap_err()
+                .to_string(),
+            "Requested AES256 server-side encryption, the response has \
+             x-amz-server-side-encryption Some(\"aws:kms\")"
+        );
+        assert!(ServerSideEncryption::Customer { key: vec![0; 32] }
+            .check_response(&headers)
+            .is_err());
+    }
+}
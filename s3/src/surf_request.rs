@@ -1,14 +1,15 @@
-use async_std::io::ReadExt;
-use std::io::Write;
+use futures::io::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
 use super::bucket::Bucket;
 use super::command::Command;
+use crate::checksum::ChecksumVerifier;
 use chrono::{DateTime, Utc};
 
 use crate::command::HttpMethod;
-use crate::request_trait::Request;
+use crate::request_trait::{check_content_length, Request, RequestTimeout};
+use crate::serde_types::S3Error;
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use http::HeaderMap;
 use maybe_async::maybe_async;
 use surf::http::headers::{HeaderName, HeaderValue};
@@ -19,6 +20,7 @@ pub struct SurfRequest<'a> {
     pub bucket: &'a Bucket,
     pub path: &'a str,
     pub command: Command<'a>,
+    /// Time the request is signed at, `Utc::now()` unless set otherwise.
     pub datetime: DateTime<Utc>,
     pub sync: bool,
 }
@@ -36,7 +38,7 @@ impl<'a> Request for SurfRequest<'a> {
         self.bucket.clone()
     }
 
-    fn command(&self) -> Command {
+    fn command(&self) -> Command<'_> {
         self.command.clone()
     }
 
@@ -45,30 +47,82 @@ impl<'a> Request for SurfRequest<'a> {
     }
 
     async fn response(&self) -> Result<surf::Response> {
+        if self.bucket.danger_accept_invalid_certs()
+            || !self.bucket.extra_root_certificates().is_empty()
+        {
+            return Err(anyhow::anyhow!(
+                "TLS settings are not supported with the with-async-std feature"
+            ));
+        }
+
         // Build headers
         let headers = self.headers()?;
 
-        let request = match self.command.http_verb() {
-            HttpMethod::Get => surf::Request::builder(Method::Get, self.url()),
-            HttpMethod::Delete => surf::Request::builder(Method::Delete, self.url()),
-            HttpMethod::Put => surf::Request::builder(Method::Put, self.url()),
-            HttpMethod::Post => surf::Request::builder(Method::Post, self.url()),
-            HttpMethod::Head => surf::Request::builder(Method::Head, self.url()),
+        let method = match self.command.http_verb() {
+            HttpMethod::Get => Method::Get,
+            HttpMethod::Delete => Method::Delete,
+            HttpMethod::Put => Method::Put,
+            HttpMethod::Post => Method::Post,
+            HttpMethod::Head => Method::Head,
         };
+        let url = self.url();
+        let body = self.request_body();
+
+        let mut attempt = 1;
+        let mut response = loop {
+            // Cloning a surf request drops its body, so it is built again for
+            // every attempt
+            let mut request = surf::Request::builder(method, url.clone()).body(body.clone());
+
+            for (name, value) in headers.iter() {
+                request = request.header(
+                    HeaderName::from_bytes(AsRef::<[u8]>::as_ref(&name).to_vec()).unwrap(),
+                    HeaderValue::from_bytes(AsRef::<[u8]>::as_ref(&value).to_vec()).unwrap(),
+                );
+            }
 
-        let mut request = request.body(self.request_body());
-
-        for (name, value) in headers.iter() {
-            request = request.header(
-                HeaderName::from_bytes(AsRef::<[u8]>::as_ref(&name).to_vec()).unwrap(),
-                HeaderValue::from_bytes(AsRef::<[u8]>::as_ref(&value).to_vec()).unwrap(),
-            );
-        }
-
-        let response = request.send().await.unwrap();
+            let response = match self.bucket.request_timeout() {
+                Some(timeout) => async_std::future::timeout(timeout, request.send())
+                    .await
+                    .map_err(|_| RequestTimeout { timeout })?,
+                None => request.send().await,
+            };
+            // Surf only fails without a response on connection errors
+            let response = match response {
+                Ok(response) => response,
+                Err(e) => match self
+                    .bucket
+                    .retry_policy()
+                    .network_error_delay(attempt, &self.command.http_verb())
+                {
+                    Some(delay) => {
+                        async_std::task::sleep(delay).await;
+                        attempt += 1;
+                        continue;
+                    }
+                    None => return Err(e.into_inner()),
+                },
+            };
+            let retry_after = response.header("Retry-After").map(|value| value.as_str());
+            match self.bucket.retry_policy().retry_delay(
+                attempt,
+                &self.command.http_verb(),
+                response.status().into(),
+                retry_after,
+            ) {
+                Some(delay) => async_std::task::sleep(delay).await,
+                None => break response,
+            }
+            attempt += 1;
+        };
 
-        if cfg!(feature = "fail-on-err") && !response.status().is_success() {
-            return Err(anyhow!("Request failed with code {}", response.status()));
+        if cfg!(feature = "fail-on-err")
+            && !response.status().is_success()
+            && !self.command.is_expected_status(response.status().into())
+        {
+            let status = response.status().into();
+            let body = response.body_bytes().await.unwrap_or_default();
+            return Err(S3Error::from_response(status, &body).into());
         }
 
         Ok(response)
@@ -77,7 +131,11 @@ impl<'a> Request for SurfRequest<'a> {
     async fn response_data(&self, etag: bool) -> Result<(Vec<u8>, u16)> {
         let mut response = self.response().await?;
         let status_code = response.status();
-        let body = response.body_bytes().await.unwrap();
+        let body = response
+            .body_bytes()
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        check_content_length(&self.command, content_length(&response), body.len() as u64)?;
         let mut body_vec = Vec::new();
         body_vec.extend_from_slice(&body[..]);
         if etag {
@@ -88,38 +146,78 @@ impl<'a> Request for SurfRequest<'a> {
         Ok((body_vec, status_code.into()))
     }
 
-    async fn response_data_to_writer<T: Write + Send>(&self, writer: &mut T) -> Result<u16> {
-        let mut buffer = Vec::new();
-
-        let response = self.response().await?;
+    async fn response_data_to_writer<T: AsyncWrite + Send + Unpin>(
+        &self,
+        writer: &mut T,
+    ) -> Result<(u64, u16)> {
+        let mut response = self.response().await?;
 
         let status_code = response.status();
+        let length = content_length(&response).map(str::to_owned);
+        let mut verifier = self
+            .bucket
+            .checksum_algorithm()
+            .map(|_| ChecksumVerifier::new(&header_map(&response)));
+
+        let mut written = 0;
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            let read = response.read(&mut buffer).await?;
+            if read == 0 {
+                break;
+            }
+            writer.write_all(&buffer[..read]).await?;
+            if let Some(verifier) = &mut verifier {
+                verifier.update(&buffer[..read]);
+            }
+            written += read as u64;
+        }
+        writer.flush().await?;
+        check_content_length(&self.command, length.as_deref(), written)?;
+        if let Some(verifier) = verifier {
+            verifier.verify()?;
+        }
 
-        let mut stream = surf::http::Body::from_reader(response, None);
-
-        stream.read_to_end(&mut buffer).await?;
-
-        writer.write_all(&buffer)?;
-
-        Ok(status_code.into())
+        Ok((written, status_code.into()))
     }
 
     async fn response_header(&self) -> Result<(HeaderMap, u16)> {
-        let mut header_map = HeaderMap::new();
         let response = self.response().await?;
         let status_code = response.status();
+        Ok((header_map(&response), status_code.into()))
+    }
 
-        for (name, value) in response.iter() {
-            header_map.insert(
-                http::header::HeaderName::from_lowercase(
-                    name.to_string().to_ascii_lowercase().as_ref(),
-                )
-                .unwrap(),
-                value.as_str().parse().unwrap(),
-            );
-        }
-        Ok((header_map, status_code.into()))
+    async fn response_data_with_headers(&self) -> Result<(Vec<u8>, HeaderMap, u16)> {
+        let mut response = self.response().await?;
+        let status_code = response.status();
+        let headers = header_map(&response);
+        let body = response
+            .body_bytes()
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        check_content_length(&self.command, content_length(&response), body.len() as u64)?;
+        Ok((body, headers, status_code.into()))
+    }
+}
+
+fn content_length(response: &surf::Response) -> Option<&str> {
+    response
+        .header("Content-Length")
+        .map(|value| value.as_str())
+}
+
+fn header_map(response: &surf::Response) -> HeaderMap {
+    let mut header_map = HeaderMap::new();
+    for (name, value) in response.iter() {
+        header_map.insert(
+            http::header::HeaderName::from_lowercase(
+                name.to_string().to_ascii_lowercase().as_ref(),
+            )
+            .unwrap(),
+            value.as_str().parse().unwrap(),
+        );
     }
+    header_map
 }
 
 impl<'a> SurfRequest<'a> {
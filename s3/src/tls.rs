@@ -0,0 +1,64 @@
+//! TLS settings of the connections to custom endpoints, such as on-prem
+//! MinIO or Ceph deployments using a private certificate authority.
+
+use anyhow::{anyhow, Result};
+
+/// A root certificate trusted on top of the system ones, see
+/// `Bucket::with_extra_root_certificate`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Certificate {
+    der: Vec<u8>,
+}
+
+impl Certificate {
+    /// A DER encoded certificate.
+    pub fn from_der(der: &[u8]) -> Certificate {
+        Certificate { der: der.to_vec() }
+    }
+
+    /// The first certificate of a PEM file.
+    pub fn from_pem(pem: &[u8]) -> Result<Certificate> {
+        let pem = std::str::from_utf8(pem)?;
+        let start = pem
+            .find("-----BEGIN CERTIFICATE-----")
+            .ok_or_else(|| anyhow!("No certificate found in PEM"))?
+            + "-----BEGIN CERTIFICATE-----".len();
+        let end = pem[start..]
+            .find("-----END CERTIFICATE-----")
+            .ok_or_else(|| anyhow!("Unterminated certificate in PEM"))?
+            + start;
+        let base64: String = pem[start..end]
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        Ok(Certificate {
+            der: base64::decode(base64)?,
+        })
+    }
+
+    /// The DER encoding of the certificate.
+    pub fn der(&self) -> &[u8] {
+        &self.der
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_certificate_from_pem() {
+        let pem = b"subject=CN = test\n\
+                    -----BEGIN CERTIFICATE-----\n\
+                    AQID\n\
+                    BAU=\n\
+                    -----END CERTIFICATE-----\n";
+        assert_eq!(Certificate::from_pem(pem).unwrap().der(), [1, 2, 3, 4, 5]);
+        assert_eq!(
+            Certificate::from_pem(pem).unwrap(),
+            Certificate::from_der(&[1, 2, 3, 4, 5])
+        );
+        assert!(Certificate::from_pem(b"-----BEGIN CERTIFICATE-----\nAQID\n").is_err());
+        assert!(Certificate::from_pem(b"").is_err());
+    }
+}
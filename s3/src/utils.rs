@@ -1,7 +1,12 @@
 use std::str::FromStr;
 
-use crate::{bucket::CHUNK_SIZE, serde_types::HeadObjectResult};
+use crate::{
+    bucket::CHUNK_SIZE,
+    serde_types::{ContentRange, GetObjectResult, HeadObjectResult},
+};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 
 #[cfg(feature = "with-async-std")]
 use async_std::fs::File;
@@ -89,8 +94,17 @@ pub fn etag_for_path(path: impl AsRef<Path>) -> Result<String> {
 
 #[cfg(any(feature = "tokio", feature = "async-std"))]
 pub async fn read_chunk<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<u8>> {
-    let mut chunk = Vec::with_capacity(CHUNK_SIZE);
-    let mut take = reader.take(CHUNK_SIZE as u64);
+    read_chunk_with_size(reader, CHUNK_SIZE).await
+}
+
+/// Read up to `size` bytes, less only at the end of `reader`.
+#[cfg(any(feature = "tokio", feature = "async-std"))]
+pub async fn read_chunk_with_size<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    size: usize,
+) -> Result<Vec<u8>> {
+    let mut chunk = Vec::with_capacity(size);
+    let mut take = reader.take(size as u64);
     take.read_to_end(&mut chunk).await?;
 
     Ok(chunk)
@@ -98,8 +112,14 @@ pub async fn read_chunk<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<u8>>
 
 #[cfg(feature = "sync")]
 pub fn read_chunk<R: Read>(reader: &mut R) -> Result<Vec<u8>> {
-    let mut chunk = Vec::with_capacity(CHUNK_SIZE);
-    let mut take = reader.take(CHUNK_SIZE as u64);
+    read_chunk_with_size(reader, CHUNK_SIZE)
+}
+
+/// Read up to `size` bytes, less only at the end of `reader`.
+#[cfg(feature = "sync")]
+pub fn read_chunk_with_size<R: Read>(reader: &mut R, size: usize) -> Result<Vec<u8>> {
+    let mut chunk = Vec::with_capacity(size);
+    let mut take = reader.take(size as u64);
     take.read_to_end(&mut chunk)?;
 
     Ok(chunk)
@@ -135,18 +155,7 @@ impl From<&http::HeaderMap> for HeadObjectResult {
             last_modified: headers.get_string("Last-Modified"),
             ..Default::default()
         };
-        let mut values = ::std::collections::HashMap::new();
-        for (key, value) in headers.iter() {
-            if key.as_str().starts_with("x-amz-meta-") {
-                if let Ok(value) = value.to_str() {
-                    values.insert(
-                        key.as_str()["x-amz-meta-".len()..].to_owned(),
-                        value.to_owned(),
-                    );
-                }
-            }
-        }
-        result.metadata = Some(values);
+        result.metadata = Some(metadata(headers));
         result.missing_meta = headers.get_and_convert("x-amz-missing-meta");
         result.object_lock_legal_hold_status = headers.get_string("x-amz-object-lock-legal-hold");
         result.object_lock_mode = headers.get_string("x-amz-object-lock-mode");
@@ -169,6 +178,64 @@ impl From<&http::HeaderMap> for HeadObjectResult {
     }
 }
 
+impl GetObjectResult {
+    pub(crate) fn new(data: Vec<u8>, headers: http::HeaderMap) -> Self {
+        GetObjectResult {
+            data,
+            content_type: headers.get_string("Content-Type"),
+            content_length: headers.get_and_convert("Content-Length"),
+            etag: headers.get_string("ETag"),
+            last_modified: headers
+                .get_string("Last-Modified")
+                .and_then(|date| DateTime::parse_from_rfc2822(&date).ok())
+                .map(|date| date.with_timezone(&Utc)),
+            metadata: metadata(&headers),
+            content_range: headers
+                .get_string("Content-Range")
+                .and_then(|range| range.parse().ok()),
+            headers,
+        }
+    }
+}
+
+impl FromStr for ContentRange {
+    type Err = anyhow::Error;
+
+    /// Parse a `Content-Range` such as `bytes 0-31/1024`, or `bytes 0-31/*`
+    /// when the size of the object is unknown.
+    fn from_str(value: &str) -> Result<Self> {
+        let parse = || -> Option<ContentRange> {
+            let (range, total_size) = value.trim().strip_prefix("bytes ")?.split_once('/')?;
+            let (start, end) = range.split_once('-')?;
+            Some(ContentRange {
+                start: start.parse().ok()?,
+                end: end.parse().ok()?,
+                total_size: match total_size {
+                    "*" => None,
+                    size => Some(size.parse().ok()?),
+                },
+            })
+        };
+        parse().ok_or_else(|| anyhow::anyhow!("Invalid Content-Range {:?}", value))
+    }
+}
+
+/// User metadata, from the `x-amz-meta-*` headers without their prefix.
+fn metadata(headers: &http::HeaderMap) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    for (key, value) in headers.iter() {
+        if key.as_str().starts_with("x-amz-meta-") {
+            if let Ok(value) = value.to_str() {
+                values.insert(
+                    key.as_str()["x-amz-meta-".len()..].to_owned(),
+                    value.to_owned(),
+                );
+            }
+        }
+    }
+    values
+}
+
 #[cfg(test)]
 mod test {
     use crate::utils::etag_for_path;
@@ -183,6 +250,100 @@ mod test {
         (0..size).map(|_| 33).collect()
     }
 
+    #[test]
+    fn test_get_object_result_headers() {
+        use crate::serde_types::GetObjectResult;
+        use chrono::{TimeZone, Utc};
+
+        let mut headers = http::HeaderMap::new();
+        headers.insert("content-type", "text/plain".parse().unwrap());
+        headers.insert("content-length", "5".parse().unwrap());
+        headers.insert(
+            "etag",
+            "\"5d41402abc4b2a76b9719d911017c592\"".parse().unwrap(),
+        );
+        headers.insert(
+            "last-modified",
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        headers.insert("x-amz-meta-author", "me".parse().unwrap());
+
+        let object = GetObjectResult::new(b"hello".to_vec(), headers);
+        assert_eq!(object.data, b"hello");
+        assert_eq!(object.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(object.content_length, Some(5));
+        assert_eq!(
+            object.etag.as_deref(),
+            Some("\"5d41402abc4b2a76b9719d911017c592\"")
+        );
+        assert_eq!(
+            object.last_modified,
+            Some(Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, 0).unwrap())
+        );
+        assert_eq!(object.metadata["author"], "me");
+        assert_eq!(object.content_range, None);
+        assert_eq!(object.headers["x-amz-meta-author"], "me");
+    }
+
+    #[test]
+    fn test_head_object_result_headers() {
+        use crate::serde_types::HeadObjectResult;
+
+        let mut headers = http::HeaderMap::new();
+        headers.insert("content-type", "image/png".parse().unwrap());
+        headers.insert("content-length", "1024".parse().unwrap());
+        headers.insert("etag", "\"abc\"".parse().unwrap());
+        headers.insert(
+            "last-modified",
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        headers.insert("x-amz-meta-author", "me".parse().unwrap());
+        headers.insert("x-amz-storage-class", "GLACIER".parse().unwrap());
+        headers.insert(
+            "x-amz-server-side-encryption-customer-key-md5",
+            "cLyPS3KoaSFGi/joRB3OUQ==".parse().unwrap(),
+        );
+
+        let head = HeadObjectResult::from(&headers);
+        assert_eq!(head.content_type.as_deref(), Some("image/png"));
+        assert_eq!(head.content_length, Some(1024));
+        assert_eq!(head.e_tag.as_deref(), Some("\"abc\""));
+        assert_eq!(
+            head.last_modified.as_deref(),
+            Some("Wed, 21 Oct 2015 07:28:00 GMT")
+        );
+        assert_eq!(head.metadata.unwrap()["author"], "me");
+        assert_eq!(head.storage_class.as_deref(), Some("GLACIER"));
+        assert_eq!(
+            head.sse_customer_key_md5.as_deref(),
+            Some("cLyPS3KoaSFGi/joRB3OUQ==")
+        );
+        assert_eq!(head.delete_marker, None);
+    }
+
+    #[test]
+    fn test_content_range() {
+        use crate::serde_types::ContentRange;
+
+        assert_eq!(
+            "bytes 0-31/1024".parse::<ContentRange>().unwrap(),
+            ContentRange {
+                start: 0,
+                end: 31,
+                total_size: Some(1024)
+            }
+        );
+        assert_eq!(
+            "bytes 100-199/*"
+                .parse::<ContentRange>()
+                .unwrap()
+                .total_size,
+            None
+        );
+        assert!("bytes */1024".parse::<ContentRange>().is_err());
+        assert!("0-31/1024".parse::<ContentRange>().is_err());
+    }
+
     #[maybe_async::test(
         feature = "sync",
         async(all(not(feature = "sync"), feature = "with-tokio"), tokio::test),
@@ -193,7 +354,7 @@ mod test {
     )]
     async fn test_etag() {
         let path = "test_etag";
-        std::fs::remove_file(path).unwrap_or_else(|_| {});
+        std::fs::remove_file(path).unwrap_or(());
         let test: Vec<u8> = object(10_000_000);
 
         let mut file = File::create(path).unwrap();
@@ -201,7 +362,7 @@ mod test {
 
         let etag = etag_for_path(path).await.unwrap();
 
-        std::fs::remove_file(path).unwrap_or_else(|_| {});
+        std::fs::remove_file(path).unwrap_or(());
 
         assert_eq!(etag, "e438487f09f09c042b2de097765e5ac2-2");
     }